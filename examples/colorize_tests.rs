@@ -0,0 +1,487 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2013 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+#![crate_name = "colorize_tests"]
+
+extern crate colorize;
+use colorize::AnsiColor;
+
+//use colorize::{Red, Greenb};
+
+fn main() {
+    //colorize::global_fg(Red);
+    //colorize::global_bg(Greenb);
+    println!("{}", "\tTest foreground color for strbuf".b_green());
+    tests::foreground_color_strbuf();
+    println!("{}", "\tTest background color for strbuf".greenb());
+    tests::background_color_strbuf();
+    println!("{}", "\tTest foreground color for &'static str".b_green());
+    tests::foreground_color_ref_str();
+    println!("{}", "\tTest background color for &'static str".greenb());
+    tests::background_color_ref_str();
+    println!("{}", "\tTest custom styles for owned str".b_green());
+    tests::custom_styles_strbuf();
+    println!("{}", "\tTest custom styles for ref str".b_green());
+    tests::custom_styles_ref_str();
+    println!("{}", "\tTest gradients".b_green());
+    tests::gradients();
+    println!("{}", "\tTest progress bar".b_green());
+    tests::progress_bar();
+    println!("{}", "\tTest spinners".b_green());
+    tests::spinners();
+    println!("{}", "\tTest tables".b_green());
+    tests::tables();
+    println!("{}", "\tTest wrap and truncate".b_green());
+    tests::wrap_truncate();
+    println!("{}", "\tTest format specs".b_green());
+    tests::format_specs();
+    println!("{}", "\tTest diffs".b_green());
+    tests::diffs();
+    println!("{}", "\tTest json".b_green());
+    tests::json();
+    println!("{}", "\tTest debug".b_green());
+    tests::debug();
+    println!("{}", "\tTest terminal appearance".b_green());
+    tests::appearance();
+    println!("{}", "\tTest operating system commands".b_green());
+    tests::osc();
+    println!("{}", "\tTest multiplexers passthrough".b_green());
+    tests::multiplexers();
+    println!("{}", "\tTest contrast".b_green());
+    tests::contrast();
+    println!("{}", "\tTest color vision deficiencies".b_green());
+    tests::cvd();
+    println!("{}", "\tTest color manipulation".b_green());
+    tests::color_manipulation();
+    println!("{}", "\tTest const paints".b_green());
+    tests::const_paints();
+    println!("{}", "\tTest parsed paints".b_green());
+    tests::parsed_paints();
+    println!("{}", "\tTest conversion to html".b_green());
+    tests::conversion();
+    println!("{}", "\tTest terminfo capabilities".b_green());
+    tests::terminfo();
+    println!("{}", "\tTest styled strings".b_green());
+    tests::styled_strings();
+    tests::final_test();
+}
+
+mod tests {
+    use colorize;
+    use colorize::{AnsiColor, Rgb, ColorDepth, ProgressBar, Spinner, Frames};
+    use colorize::{Table, Align, Border, Padding, Diff, JsonPrinter};
+    use colorize::Color::*;
+    use colorize::BgColor::*;
+    use colorize::Style::*;
+    use std::io::Write;
+    use std::thread;
+    use std::time::Duration;
+
+    pub fn foreground_color_strbuf() {
+        println!("{}", "Black".to_string().black());
+        println!("{}", "Bright black".to_string().b_black());
+        println!("{}", "Red".to_string().red());
+        println!("{}", "Bright Red".to_string().b_red());
+        println!("{}", "Green".to_string().green());
+        println!("{}", "Bright Green".to_string().b_green());
+        println!("{}", "Yellow".to_string().yellow());
+        println!("{}", "Bright Yellow".to_string().b_yellow());
+        println!("{}", "Blue".to_string().blue());
+        println!("{}", "Bright Blue".to_string().b_blue());
+        println!("{}", "Magenta".to_string().magenta());
+        println!("{}", "Bright Magenta".to_string().b_magenta());
+        println!("{}", "Cyan".to_string().cyan());
+        println!("{}", "Bright Cyan".to_string().b_cyan());
+        println!("{}", "Grey".to_string().grey());
+        println!("{}", "Bright Grey".to_string().b_grey());
+        println!("{}", "Hello world".to_string().default());
+    }
+
+    pub fn background_color_strbuf() {
+        println!("{}", "Black".to_string().blackb());
+        println!("{}", "Bright black".to_string().b_blackb());
+        println!("{}", "Red".to_string().redb());
+        println!("{}", "Bright Red".to_string().b_redb());
+        println!("{}", "Green".to_string().greenb());
+        println!("{}", "Bright Green".to_string().b_greenb());
+        println!("{}", "Yellow".to_string().yellowb());
+        println!("{}", "Bright Yellow".to_string().b_yellowb());
+        println!("{}", "Blue".to_string().blueb());
+        println!("{}", "Bright Blue".to_string().b_blueb());
+        println!("{}", "Magenta".to_string().magentab());
+        println!("{}", "Bright Magenta".to_string().b_magentab());
+        println!("{}", "Cyan".to_string().cyanb());
+        println!("{}", "Bright Cyan".to_string().b_cyanb());
+        println!("{}", "Grey".to_string().greyb());
+        println!("{}", "Bright Grey".to_string().b_greyb());
+        println!("{}", "Hello world".to_string().defaultb());
+    }
+
+    pub fn foreground_color_ref_str() {
+        println!("{}", "Black".black());
+        println!("{}", "Bright black".b_black());
+        println!("{}", "Red".red());
+        println!("{}", "Bright Red".b_red());
+        println!("{}", "Green".green());
+        println!("{}", "Bright Green".b_green());
+        println!("{}", "Yellow".yellow());
+        println!("{}", "Bright Yellow".b_yellow());
+        println!("{}", "Blue".blue());
+        println!("{}", "Bright Blue".b_blue());
+        println!("{}", "Magenta".magenta());
+        println!("{}", "Bright Magenta".b_magenta());
+        println!("{}", "Cyan".cyan());
+        println!("{}", "Bright Cyan".b_cyan());
+        println!("{}", "Grey".grey());
+        println!("{}", "Bright Grey".b_grey());
+        println!("{}", "Hello world".default());
+    }
+
+    pub fn background_color_ref_str() {
+        println!("{}", "Black".blackb());
+        println!("{}", "Bright black".b_blackb());
+        println!("{}", "Red".redb());
+        println!("{}", "Bright Red".b_redb());
+        println!("{}", "Green".greenb());
+        println!("{}", "Bright Green".b_greenb());
+        println!("{}", "Yellow".yellowb());
+        println!("{}", "Bright Yellow".b_yellowb());
+        println!("{}", "Blue".blueb());
+        println!("{}", "Bright Blue".b_blueb());
+        println!("{}", "Magenta".magentab());
+        println!("{}", "Bright Magenta".b_magentab());
+        println!("{}", "Cyan".cyanb());
+        println!("{}", "Bright Cyan".b_cyanb());
+        println!("{}", "Grey".greyb());
+        println!("{}", "Bright Grey".b_greyb());
+        println!("{}", "Hello world".defaultb());
+    }
+
+    pub fn custom_styles_strbuf() {
+        println!("{}", "Hello world".to_string().underlined());
+        println!("{}", "hello world".to_string().bold());
+        println!("{}", "Hello world".to_string().blink());
+        println!("{}", "Hello world".to_string().reverse());
+        println!("{}", "Hello world".to_string().concealed());
+    }
+
+    pub fn custom_styles_ref_str() {
+        println!("{}", "Hello world".underlined());
+        println!("{}", "Hello world".bold());
+        println!("{}", "Hello world".blink());
+        println!("{}", "Hello world".reverse());
+        println!("{}", "Hello world".concealed());
+    }
+
+    pub fn gradients() {
+        let stops = [Rgb::new(255, 0, 0), Rgb::new(0, 0, 255)];
+        println!("{}", "Red to blue gradient".gradient(&stops));
+        println!("{}", "Red to blue background".gradientb(&stops));
+        println!("{}", "Rainbow, with accents e\u{301}e\u{301}".to_string().gradient(
+            &[Red.into(), Yellow.into(), Green.into(), Blue.into()]));
+        println!("{}", "Bold gradient".bold().gradient(&stops));
+        colorize::set_color_depth(Some(ColorDepth::Ansi256));
+        println!("{}", "256 colors gradient".gradient(&stops));
+        colorize::set_color_depth(Some(ColorDepth::Ansi16));
+        println!("{}", "16 colors gradient".gradient(&stops));
+        colorize::set_color_depth(None);
+    }
+
+    pub fn progress_bar() {
+        let mut bar = ProgressBar::new(200).width(30).color(Cyan);
+        bar.set_message("Downloading");
+        for _ in 0..200 {
+            bar.inc(1);
+            thread::sleep(Duration::from_millis(5));
+        }
+        bar.finish();
+    }
+
+    pub fn spinners() {
+        let spinner = Spinner::start(Frames::Dots, "Working".blue());
+        thread::sleep(Duration::from_millis(500));
+        spinner.success("Done");
+        let spinner = Spinner::with_color(Frames::Arc, Magenta, "Failing".to_string());
+        thread::sleep(Duration::from_millis(500));
+        spinner.failure("Failed");
+    }
+
+    pub fn tables() {
+        let mut table = Table::new()
+            .header(vec!["Service", "Status", "Latency", "Notes"])
+            .header_style(Bold)
+            .border(Border::Rounded)
+            .align(2, Align::Right)
+            .max_width(3, 16)
+            .zebra(Blackb);
+        table.add_row(vec!["database".to_string(), "up".green(), "12ms".to_string(),
+                           "primary in eu-west".to_string()]);
+        table.add_row(vec!["cache".to_string(), "down".b_red().bold(), "-".to_string(),
+                           "restarted twice in the last hour".yellow()]);
+        table.add_row(vec!["queue".to_string(), "degraded".yellow(), "340ms".to_string(),
+                           "".to_string()]);
+        print!("{}", table);
+        let mut table = Table::new().header(vec!["Key", "Value"]);
+        table.add_row(vec!["name".cyan(), "colorize".to_string()]);
+        table.add_row(vec!["宽字符".cyan(), "wide".to_string()]);
+        print!("{}", table);
+    }
+
+    pub fn wrap_truncate() {
+        let text = format!("{} and {} then {}",
+                           "Some green text that goes on".green(),
+                           "bold red".red().bold(),
+                           "a_very_long_underlined_word".underlined());
+        for line in colorize::wrap(&text, 12) {
+            println!("|{}|", line);
+        }
+        println!("{}", colorize::truncate(&text, 20, "…"));
+        println!("{}", colorize::truncate(&"日本語のテキスト".cyan(), 9, "…"));
+    }
+
+    pub fn format_specs() {
+        println!("[{:>10}] [{:<10}] [{:^10}]", "ok".green().styled(),
+                 "warn".yellow().styled(), "fail".red().styled());
+        println!("[{:*^12}] [{:.3}]", "center".cyan().styled(),
+                 "truncated".magenta().styled());
+        println!("[{:>10}]", "inside".greenb().styled().padding(Padding::Inside));
+    }
+
+    pub fn diffs() {
+        let old = "[server]\nhost = localhost\nport = 8080\nworkers = 4\n\n[log]\nlevel = info\n";
+        let new = "[server]\nhost = 0.0.0.0\nport = 8080\nworkers = 4\n\n[log]\nlevel = debug\nfile = out.log\n";
+        print!("{}", Diff::new().context(1).labels("a/config.toml", "b/config.toml")
+               .render(old, new));
+        print!("{}", Diff::new().side_by_side(60).labels("old", "new").render(old, new));
+    }
+
+    pub fn json() {
+        print!("{}", colorize::highlight_json(
+            r#"{"name": "colorize", "version": 1.5, "tags": ["ansi", "term"], "stable": true, "owner": null, "deps": {}}"#));
+        JsonPrinter::new().indent(4).highlight_stream("{\"a\": 1} [2, 3]".as_bytes(), ::std::io::stdout()).unwrap();
+        println!("{}", colorize::highlight_json(r#"{"a": [1, 2,, 3]}"#));
+        println!("{}", colorize::highlight_json(r#"{"a": "unterminated"#));
+    }
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Point { x: f32, y: f32 }
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    enum Shape { Circle { center: Point, radius: f64 }, Label(String, char), Empty }
+
+    pub fn appearance() {
+        use colorize::appearance;
+        use colorize::tty::Scripted;
+        let mut tty = Scripted::new(b"\x1b]11;rgb:fdfd/f6f6/e3e3\x1b\\\x1b]10;rgb:65/7b/83\x07");
+        println!("{:?}", appearance::detect_with(&mut tty, Duration::from_millis(50)));
+        println!("sent {:?}", String::from_utf8_lossy(&tty.written));
+        println!("{:?}", appearance::detect_with(&mut Scripted::new(b""), Duration::from_millis(50)));
+        println!("{:?}", appearance::from_colorfgbg("15;default;0"));
+        println!("{:?}", appearance::detect(Duration::from_millis(100)));
+
+        let mut tty = Scripted::new(b"\x1b]4;0;rgb:0000/2b2b/3636\x07\x1b]4;1;rgb:dc/32/2f\x1b\\");
+        println!("{:?}", appearance::query_palette_color(&mut tty, Black, Duration::from_millis(50)));
+        println!("{:?}", appearance::query_palette_color(&mut tty, Red, Duration::from_millis(50)));
+        let mut tty = Scripted::new(b"");
+        {
+            let mut guard = colorize::PaletteGuard::new(&mut tty);
+            guard.set(Red, Rgb::new(255, 85, 85)).unwrap();
+            guard.set(BrightBlue, Rgb::new(0x87, 0xaf, 0xff)).unwrap();
+        }
+        println!("sent {:?}", String::from_utf8_lossy(&tty.written));
+    }
+
+    pub fn osc() {
+        use colorize::{Osc, TitleGuard};
+        use std::path::Path;
+        let mut out = Vec::new();
+        {
+            let mut title = TitleGuard::new(&mut out, "colorize tests").unwrap();
+            title.set("colorize tests: osc").unwrap();
+        }
+        write!(out, "{}", Osc::Notify("tests done")).unwrap();
+        write!(out, "{}", Osc::NotifyWithTitle { title: "colorize", body: "tests done" }).unwrap();
+        write!(out, "{}", Osc::WorkingDirectory { host: "localhost", path: Path::new("/tmp/my dir") }).unwrap();
+        println!("{:?}", String::from_utf8_lossy(&out));
+        println!("{}", Osc::Hyperlink { url: "https://github.com/jeremyletang/colorize", text: "colorize on github" });
+    }
+
+    pub fn multiplexers() {
+        use colorize::{Multiplexer, Osc};
+        use colorize::mux;
+        println!("running in {:?}", Multiplexer::detect());
+        let title = Osc::Title("colorize tests").to_string();
+        println!("tmux: {:?}", Multiplexer::Tmux.wrap(&title));
+        println!("screen: {:?}", Multiplexer::Screen.wrap("\x1b]8;;https://example.com\x1b\\"));
+        mux::set_passthrough(true);
+        println!("{:?} {:?}", mux::passthrough("\x1b]11;?\x07"), mux::passthrough("\x1b[31m"));
+        mux::set_passthrough(false);
+    }
+
+    pub fn contrast() {
+        use colorize::contrast;
+        println!("black on white: {:.2}", colorize::contrast_ratio(Rgb::new(0, 0, 0), Rgb::new(255, 255, 255)));
+        println!("grey on white: {:.2}", contrast::contrast(Grey, BrightGreyb));
+        for &bg in [Yellowb, Blueb, Greyb, BrightBlackb, Redb].iter() {
+            let fg = colorize::readable_on(bg, &[Black, BrightGrey, Yellow, Blue]);
+            println!("{}", colorize::Paint::new().fg(fg).bg(bg).paint(String::from("readable")));
+        }
+        colorize::set_min_contrast(Some(4.5));
+        println!("{}", "hard to read".grey().greyb());
+        println!("{}", "hard to read".grey().greyb().bold());
+        println!("{}", "easy to read".black().greyb());
+        colorize::set_min_contrast(None);
+    }
+
+    pub fn cvd() {
+        use colorize::{Deficiency, Status};
+        use colorize::cvd::simulate;
+        for &d in [Deficiency::Protanopia, Deficiency::Deuteranopia, Deficiency::Tritanopia].iter() {
+            let (red, green) = (simulate(Rgb::from(Red), d), simulate(Rgb::from(Green), d));
+            println!("{:?}: red {:?} green {:?}", d, red, green);
+        }
+        for &s in [Status::Success, Status::Failure, Status::Warning, Status::Info].iter() {
+            println!("{}", s.paint("status"));
+        }
+        colorize::set_cvd_safe(true);
+        println!("{} {} {}", "passed".green(), "failed".red(), "skipped".yellowb());
+        println!("{}", Status::Failure.paint("3 tests failed"));
+        colorize::set_cvd_safe(false);
+    }
+
+    pub fn color_manipulation() {
+        let base = Rgb::from(Blue);
+        let variants = [
+            ("base", base), ("lighten", base.lighten(0.2)), ("darken", base.darken(0.2)),
+            ("saturate", Rgb::new(180, 120, 120).saturate(1.0)), ("desaturate", base.desaturate(0.7)),
+            ("mix", colorize::mix(base, Rgb::from(Yellow), 0.5)), ("complement", base.complement()),
+            ("rotate_hue", base.rotate_hue(90.0))
+        ];
+        for &(name, color) in variants.iter() {
+            println!("{} {}", "      ".rgbb(color), name.rgb(color));
+        }
+    }
+
+    const ERROR: colorize::Paint = colorize::Paint::new().fg(BrightRed).bold();
+    const NOTICE: colorize::Paint = colorize::Paint::new().fg(Black).bg(BrightCyanb).italic().underlined();
+    const ERROR_SGR: &str = ERROR.sgr().as_str();
+
+    pub fn const_paints() {
+        println!("{}", ERROR.paint(String::from("error: painted at runtime")));
+        println!("{}error: precomputed prefix\x1b[0m", ERROR_SGR);
+        println!("{:?} {:?}", NOTICE.sgr(), colorize::Paint::new().sgr());
+        println!("{}", NOTICE.paint(String::from("notice")));
+        let mut out = String::new();
+        ERROR.write(&mut out, "error: written to a fmt::Write").unwrap();
+        println!("{}", out);
+    }
+
+    pub fn parsed_paints() {
+        for spec in &["bold red", "underline bright-yellow on blue", "italic #ff8800 on #202020", "purple"] {
+            match spec.parse::<colorize::Paint>() {
+                Ok(paint) => println!("{:<32} {}", spec, paint.paint(paint.to_string())),
+                Err(e) => println!("{:<32} {}", spec, e)
+            }
+        }
+        println!("{} {}", "bright_cyan".parse::<colorize::Color>().unwrap(), "dim".parse::<colorize::Style>().unwrap());
+    }
+
+    pub fn conversion() {
+        use colorize::convert::{self, Convert};
+        let text = format!("{} and {}\n{}", "red".red(), "bold".bold(), "<escaped>".b_cyanb());
+        let mut html = colorize::HtmlWriter::new(Vec::new(), colorize::Theme::light(colorize::Palette::VGA)).unwrap();
+        convert::convert(text.as_bytes(), &mut html).unwrap();
+        html.finish().unwrap();
+        let page = String::from_utf8(html.into_inner()).unwrap();
+        for line in page.lines().filter(|l| l.contains("span")) {
+            println!("{}", line);
+        }
+    }
+
+    pub fn terminfo() {
+        use colorize::{Terminfo, Capabilities};
+        let fixtures: [&[u8]; 3] = [
+            include_bytes!("terminfo/c/colorize-8"),
+            include_bytes!("terminfo/c/colorize-direct"),
+            include_bytes!("terminfo/c/colorize-mono")
+        ];
+        for data in fixtures.iter() {
+            let info = Terminfo::parse(data).unwrap();
+            colorize::terminfo::set_capabilities(Some(Capabilities::of(&info)));
+            println!("{:<16} colors {:?}, {:?}, smulx {}: {}", info.names()[0], info.colors(), info.color_depth(),
+                     info.smulx().is_some(), "bright italic underlined".b_yellow().italic().underlined());
+        }
+        colorize::terminfo::set_capabilities(None);
+        match Terminfo::from_env() {
+            Ok(info) => println!("TERM is {}, {:?}", info.names()[0], Capabilities::of(&info)),
+            Err(e) => println!("{}", e)
+        }
+    }
+
+    pub fn styled_strings() {
+        use colorize::{StyledString, Paint};
+        use colorize::text::Render;
+
+        // lists the spans as markup
+        struct Markup(String);
+        impl Render for Markup {
+            fn span(&mut self, paint: &Paint, text: &str) {
+                if paint.is_plain() {
+                    self.0.push_str(text);
+                } else {
+                    self.0.push_str(&format!("[{}]{}[/]", paint, text));
+                }
+            }
+        }
+
+        let ansi = format!("{} {}\n{}", "error:".red().bold(), "file not found".underlined(), "hint: check the path".b_cyan());
+        let text = StyledString::from(ansi.clone());
+        println!("{}", text);
+        println!("round trip: {}, plain text kept: {}, {} spans, {} visible chars", StyledString::from(text.to_ansi()) == text,
+                 text.to_plain() == colorize::strip(&ansi), text.spans().len(), text.len_visible());
+        println!("{}|{}", text.slice(0..6), text.slice(7..11));
+        for (i, line) in text.lines().enumerate() {
+            println!("line {}: {}", i, line);
+        }
+        let words: Vec<String> = text.split(" ").map(|w| w.to_plain()).collect();
+        println!("{:?}", words);
+        let mut markup = Markup(String::new());
+        (text.replace("file", "config") + " (" + StyledString::styled("42", Paint::new().fg(Yellow)) + ")").render(&mut markup);
+        println!("{}", markup.0);
+    }
+
+    pub fn debug() {
+        let shapes = vec![
+            Shape::Circle { center: Point { x: 1.5, y: -2.0 }, radius: 1e-3 },
+            Shape::Label(String::from("hello \"world\""), 'x'),
+            Shape::Empty
+        ];
+        println!("{:#?}", colorize::debug(&shapes));
+        println!("{:?}", colorize::debug(&(Some(42u8), None::<bool>, true, Duration::from_millis(1500))));
+    }
+
+    pub fn final_test() {
+        println!("{}", "Super final test combo !".magenta().blink()
+                 .b_yellowb().underlined());
+    }
+}
@@ -0,0 +1,286 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Splitting of a text into escape sequences and visible text, and reading
+// of the parameters of sgr sequences.
+
+use alloc::string::String;
+use core::str::Split;
+
+use unicode_width::UnicodeWidthStr;
+
+use Style;
+use paint::Ink;
+
+/// A piece of a text containing escape sequences
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Token<'a> {
+    /// A complete escape sequence, including the leading ESC
+    Escape(&'a str),
+    /// Text without any escape sequence
+    Text(&'a str)
+}
+
+/// Iterator over the tokens of a text
+pub struct Tokens<'a> {
+    rest: &'a str
+}
+
+/// Split a text into escape sequences and visible text
+pub fn tokens(text: &str) -> Tokens<'_> {
+    Tokens { rest: text }
+}
+
+// Length of the escape sequence at the start of `s`, which begins with ESC
+fn escape_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.get(1) {
+        // CSI, ends with a byte in the 0x40..0x7e range
+        Some(&b'[') => {
+            bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b))
+                .map_or(bytes.len(), |p| p + 3)
+        }
+        // OSC, DCS and friends, end with BEL or ST
+        Some(&b']') | Some(&b'P') | Some(&b'_') | Some(&b'^') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    0x07 => return i + 1,
+                    0x1b if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1
+                }
+            }
+            bytes.len()
+        }
+        Some(_) => 1 + s[1..].chars().next().map_or(0, |c| c.len_utf8()),
+        None => 1
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let len = if self.rest.starts_with('\x1b') {
+            escape_len(self.rest)
+        } else {
+            self.rest.find('\x1b').unwrap_or(self.rest.len())
+        };
+        let (token, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(if token.starts_with('\x1b') { Token::Escape(token) } else { Token::Text(token) })
+    }
+}
+
+/// Is this escape sequence setting colors or styles
+pub fn is_sgr(escape: &str) -> bool {
+    escape.starts_with("\x1b[") && escape.ends_with('m')
+}
+
+/// Is this sgr sequence starting with a reset of all the attributes
+pub fn is_reset(escape: &str) -> bool {
+    let params = &escape[2..escape.len() - 1];
+    params.is_empty() || params == "0" || params.starts_with("0;")
+}
+
+/// Remove every escape sequence from a text
+pub fn strip(text: &str) -> String {
+    tokens(text).filter_map(|t| match t {
+        Token::Text(s) => Some(s),
+        Token::Escape(_) => None
+    }).collect()
+}
+
+/// Number of terminal columns used to display a text, escapes excluded
+pub fn visible_width(text: &str) -> usize {
+    tokens(text).map(|t| match t {
+        Token::Text(s) => s.width(),
+        Token::Escape(_) => 0
+    }).sum()
+}
+
+/// A change made by a parameter of a sgr sequence
+///
+/// Colors are an index in the 16 ansi colors or an rgb value, `None`
+/// standing for the default color.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SgrParam {
+    /// Remove every color and style
+    Reset,
+    Style(Style),
+    NoStyle(Style),
+    Fg(Option<Ink<u8>>),
+    Bg(Option<Ink<u8>>)
+}
+
+const SEPARATORS: &[char] = &[';', ':'];
+
+/// Iterator over the changes made by the parameters of a sgr sequence
+pub struct SgrParams<'a> {
+    params: Split<'a, &'static [char]>,
+    // a parameter changing two things
+    pending: Option<SgrParam>
+}
+
+/// Read the parameters of a sgr sequence, such as `1;38;5;208`
+///
+/// Unknown parameters are skipped, an empty one is a reset.
+pub fn sgr_params(params: &str) -> SgrParams<'_> {
+    SgrParams { params: params.split(SEPARATORS), pending: None }
+}
+
+impl<'a> SgrParams<'a> {
+    fn number(&mut self) -> Option<u16> {
+        self.params.next().map(|p| p.parse().unwrap_or(0))
+    }
+
+    // The color following a 38 or 48 parameter
+    fn extended(&mut self) -> Option<Ink<u8>> {
+        let kind = self.number();
+        let mut arg = || self.number().map_or(0, |v| v.min(255) as u8);
+        match kind {
+            Some(5) => {
+                let index = arg();
+                Some(rgb256(index).map_or(Ink::Ansi(index), |(r, g, b)| Ink::Rgb(r, g, b)))
+            }
+            Some(2) => Some(Ink::Rgb(arg(), arg(), arg())),
+            _ => None
+        }
+    }
+}
+
+impl<'a> Iterator for SgrParams<'a> {
+    type Item = SgrParam;
+
+    fn next(&mut self) -> Option<SgrParam> {
+        if let Some(param) = self.pending.take() {
+            return Some(param);
+        }
+        loop {
+            let param = match self.number()? {
+                0 => SgrParam::Reset,
+                1 => SgrParam::Style(Style::Bold),
+                2 => SgrParam::Style(Style::Faint),
+                3 => SgrParam::Style(Style::Italic),
+                4 | 21 => SgrParam::Style(Style::Underscore),
+                5 | 6 => SgrParam::Style(Style::Blink),
+                7 => SgrParam::Style(Style::Reverse),
+                8 => SgrParam::Style(Style::Concealed),
+                9 => SgrParam::Style(Style::CrossedOut),
+                22 => {
+                    self.pending = Some(SgrParam::NoStyle(Style::Faint));
+                    SgrParam::NoStyle(Style::Bold)
+                }
+                23 => SgrParam::NoStyle(Style::Italic),
+                24 => SgrParam::NoStyle(Style::Underscore),
+                25 => SgrParam::NoStyle(Style::Blink),
+                27 => SgrParam::NoStyle(Style::Reverse),
+                28 => SgrParam::NoStyle(Style::Concealed),
+                29 => SgrParam::NoStyle(Style::CrossedOut),
+                c @ 30..=37 => SgrParam::Fg(Some(Ink::Ansi(c as u8 - 30))),
+                c @ 90..=97 => SgrParam::Fg(Some(Ink::Ansi(c as u8 - 90 + 8))),
+                c @ 40..=47 => SgrParam::Bg(Some(Ink::Ansi(c as u8 - 40))),
+                c @ 100..=107 => SgrParam::Bg(Some(Ink::Ansi(c as u8 - 100 + 8))),
+                39 => SgrParam::Fg(None),
+                49 => SgrParam::Bg(None),
+                38 => match self.extended() {
+                    Some(ink) => SgrParam::Fg(Some(ink)),
+                    None => continue
+                },
+                48 => match self.extended() {
+                    Some(ink) => SgrParam::Bg(Some(ink)),
+                    None => continue
+                },
+                _ => continue
+            };
+            return Some(param);
+        }
+    }
+}
+
+/// Value of a level of the 6x6x6 cube of the 256 colors table
+pub fn cube_level(i: u8) -> u8 {
+    if i == 0 { 0 } else { 55 + i * 40 }
+}
+
+/// The rgb value of a color of the 256 colors table
+///
+/// `None` for the 16 first ones, which depend on the terminal palette.
+pub fn rgb256(index: u8) -> Option<(u8, u8, u8)> {
+    match index {
+        0..=15 => None,
+        16..=231 => {
+            let i = index - 16;
+            Some((cube_level(i / 36), cube_level(i / 6 % 6), cube_level(i % 6)))
+        }
+        _ => {
+            let v = 8 + (index - 232) * 10;
+            Some((v, v, v))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{rgb256, sgr_params, SgrParam};
+    use Style::{Bold, Faint, Italic, Underscore};
+    use paint::Ink;
+
+    fn params(s: &str) -> Vec<SgrParam> {
+        sgr_params(s).collect()
+    }
+
+    #[test]
+    fn styles_and_colors() {
+        assert_eq!(params(""), [SgrParam::Reset]);
+        assert_eq!(params("0;1;3;21"), [SgrParam::Reset, SgrParam::Style(Bold), SgrParam::Style(Italic),
+                                        SgrParam::Style(Underscore)]);
+        assert_eq!(params("22"), [SgrParam::NoStyle(Bold), SgrParam::NoStyle(Faint)]);
+        assert_eq!(params("31;102;39;49"), [SgrParam::Fg(Some(Ink::Ansi(1))), SgrParam::Bg(Some(Ink::Ansi(10))),
+                                            SgrParam::Fg(None), SgrParam::Bg(None)]);
+        assert_eq!(params("2;53;1"), [SgrParam::Style(Faint), SgrParam::Style(Bold)]);
+    }
+
+    #[test]
+    fn extended_colors() {
+        assert_eq!(params("38;5;9"), [SgrParam::Fg(Some(Ink::Ansi(9)))]);
+        assert_eq!(params("48;5;208;1"), [SgrParam::Bg(Some(Ink::Rgb(255, 135, 0))), SgrParam::Style(Bold)]);
+        assert_eq!(params("38;2;1;2;3;4"), [SgrParam::Fg(Some(Ink::Rgb(1, 2, 3))), SgrParam::Style(Underscore)]);
+        assert_eq!(params("38:2:300:2:3"), [SgrParam::Fg(Some(Ink::Rgb(255, 2, 3)))]);
+        assert_eq!(params("38;2;1"), [SgrParam::Fg(Some(Ink::Rgb(1, 0, 0)))]);
+        assert_eq!(params("38;7;1"), [SgrParam::Style(Bold)]);
+    }
+
+    #[test]
+    fn colors_table() {
+        assert_eq!(rgb256(15), None);
+        assert_eq!(rgb256(16), Some((0, 0, 0)));
+        assert_eq!(rgb256(196), Some((255, 0, 0)));
+        assert_eq!(rgb256(231), Some((255, 255, 255)));
+        assert_eq!(rgb256(232), Some((8, 8, 8)));
+        assert_eq!(rgb256(255), Some((238, 238, 238)));
+    }
+}
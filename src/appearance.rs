@@ -0,0 +1,305 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Colors of the terminal: light or dark background and the 16 colors palette.
+//
+// ```Rust
+// match colorize::appearance::detect(Duration::from_millis(100)) {
+//     Some(ref a) if a.brightness == Brightness::Light => ...,
+//     _ => ...
+// }
+// ```
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+use Color;
+use rgb::{self, Rgb, Oklab, Palette};
+use mux;
+use tty::{self, Tty};
+
+/// Is a color light or dark
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Brightness {
+    Light,
+    Dark
+}
+
+impl Brightness {
+    /// The brightness of a color, by its perceived lightness
+    pub fn of(rgb: Rgb) -> Brightness {
+        if Oklab::from(rgb).l >= 0.6 { Brightness::Light } else { Brightness::Dark }
+    }
+}
+
+/// The colors of a terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Appearance {
+    /// The brightness of the background
+    pub brightness: Brightness,
+    pub background: Option<Rgb>,
+    pub foreground: Option<Rgb>
+}
+
+fn query_color<T: Tty>(tty: &mut T, code: u8, timeout: Duration) -> io::Result<Option<Rgb>> {
+    let reply = tty::query_osc(tty, &format!("{};?", code), timeout)?;
+    let prefix = format!("{};", code);
+    Ok(reply.and_then(|r| r.strip_prefix(prefix.as_str()).and_then(Rgb::from_x11)))
+}
+
+/// Ask the terminal for its background color, with OSC 11
+pub fn query_background<T: Tty>(tty: &mut T, timeout: Duration) -> io::Result<Option<Rgb>> {
+    query_color(tty, 11, timeout)
+}
+
+/// Ask the terminal for its foreground color, with OSC 10
+pub fn query_foreground<T: Tty>(tty: &mut T, timeout: Duration) -> io::Result<Option<Rgb>> {
+    query_color(tty, 10, timeout)
+}
+
+/// The appearance described by a `COLORFGBG` value, such as `15;0`
+///
+/// The colors are resolved through the xterm palette.
+pub fn from_colorfgbg(value: &str) -> Option<Appearance> {
+    let index = |part: Option<&str>| part.and_then(|p| p.parse::<usize>().ok()).filter(|&i| i < 16);
+    // some terminals put a default field between the two colors
+    let (fg, bg) = match value.rfind(';') {
+        Some(i) => (index(value.split(';').next()), index(Some(&value[i + 1..]))?),
+        None => (None, index(Some(value))?)
+    };
+    let background = Palette::XTERM.colors[bg];
+    Some(Appearance {
+        brightness: if bg == 7 || bg >= 9 { Brightness::Light } else { Brightness::Dark },
+        background: Some(background),
+        foreground: fg.map(|i| Palette::XTERM.colors[i])
+    })
+}
+
+/// Detect the appearance of a terminal
+///
+/// The terminal is asked for its colors, each reply is awaited at most
+/// `timeout`. Without a reply the `COLORFGBG` variable is used.
+pub fn detect_with<T: Tty>(tty: &mut T, timeout: Duration) -> Option<Appearance> {
+    if let Ok(Some(background)) = query_background(tty, timeout) {
+        return Some(Appearance {
+            brightness: Brightness::of(background),
+            background: Some(background),
+            foreground: query_foreground(tty, timeout).ok().and_then(|fg| fg)
+        });
+    }
+    env::var("COLORFGBG").ok().and_then(|v| from_colorfgbg(&v))
+}
+
+/// Detect the appearance of the controlling terminal, see `detect_with`
+///
+/// The terminal is only queried when stdin and stdout are terminals.
+pub fn detect(timeout: Duration) -> Option<Appearance> {
+    #[cfg(unix)]
+    {
+        if io::stdin().is_terminal() && io::stdout().is_terminal() {
+            if let Ok(mut tty) = tty::DevTty::open() {
+                return detect_with(&mut tty, timeout);
+            }
+        }
+    }
+    let _ = timeout;
+    env::var("COLORFGBG").ok().and_then(|v| from_colorfgbg(&v))
+}
+
+fn query_index<T: Tty>(tty: &mut T, index: usize, timeout: Duration) -> io::Result<Option<Rgb>> {
+    let reply = tty::query_osc(tty, &format!("4;{};?", index), timeout)?;
+    let prefix = format!("4;{};", index);
+    Ok(reply.and_then(|r| r.strip_prefix(prefix.as_str()).and_then(Rgb::from_x11)))
+}
+
+/// Ask the terminal which rgb value it shows for a color, with OSC 4
+///
+/// `Default` has no palette entry and gives `None`.
+pub fn query_palette_color<T: Tty>(tty: &mut T, color: Color, timeout: Duration) -> io::Result<Option<Rgb>> {
+    match rgb::ansi_index(color) {
+        Some(index) => query_index(tty, index, timeout),
+        None => Ok(None)
+    }
+}
+
+/// Ask the terminal for its 16 colors palette
+///
+/// Returns `None` if the terminal does not reply, entries it does not
+/// report are taken from the xterm palette.
+pub fn query_palette<T: Tty>(tty: &mut T, timeout: Duration) -> io::Result<Option<Palette>> {
+    let mut palette = Palette::XTERM;
+    let mut replied = false;
+    for (index, entry) in palette.colors.iter_mut().enumerate() {
+        if let Some(rgb) = query_index(tty, index, timeout)? {
+            *entry = rgb;
+            replied = true;
+        } else if !replied {
+            return Ok(None);
+        }
+    }
+    Ok(Some(palette))
+}
+
+/// Change the rgb value the terminal shows for a color, with OSC 4
+///
+/// The change lasts until the palette is reset, see `PaletteGuard` to
+/// restore it automatically.
+pub fn set_palette_color<T: Tty>(tty: &mut T, color: Color, value: Rgb) -> io::Result<()> {
+    match rgb::ansi_index(color) {
+        Some(index) => {
+            let request = format!("\x1b]4;{};rgb:{:02x}/{:02x}/{:02x}\x07", index, value.r, value.g, value.b);
+            tty.write_all(mux::passthrough(&request).as_bytes())
+        }
+        None => Ok(())
+    }
+}
+
+/// Reset colors of the palette to the terminal defaults, with OSC 104
+///
+/// An empty list resets the whole palette.
+pub fn reset_palette<T: Tty>(tty: &mut T, colors: &[Color]) -> io::Result<()> {
+    let mut request = String::from("\x1b]104");
+    for index in colors.iter().filter_map(|&c| rgb::ansi_index(c)) {
+        request.push_str(&format!(";{}", index));
+    }
+    request.push('\x07');
+    tty.write_all(mux::passthrough(&request).as_bytes())
+}
+
+/// Palette changes undone when the guard is dropped
+///
+/// Only the colors changed through the guard are reset.
+pub struct PaletteGuard<T: Tty> {
+    tty: T,
+    changed: Vec<Color>
+}
+
+impl<T: Tty> PaletteGuard<T> {
+    /// Create a guard writing to a terminal
+    pub fn new(tty: T) -> PaletteGuard<T> {
+        PaletteGuard { tty, changed: Vec::new() }
+    }
+
+    /// Change the rgb value of a color, until the guard is dropped
+    pub fn set(&mut self, color: Color, value: Rgb) -> io::Result<()> {
+        set_palette_color(&mut self.tty, color, value)?;
+        if !self.changed.contains(&color) {
+            self.changed.push(color);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Tty> Drop for PaletteGuard<T> {
+    fn drop(&mut self) {
+        if !self.changed.is_empty() {
+            let _ = reset_palette(&mut self.tty, &self.changed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{detect_with, from_colorfgbg, query_background, query_palette, query_palette_color,
+                reset_palette, set_palette_color, Brightness, PaletteGuard};
+    use Color::{BrightGrey, Default, Red};
+    use rgb::{Palette, Rgb};
+    use tty::Scripted;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn colorfgbg() {
+        let a = from_colorfgbg("15;0").unwrap();
+        assert_eq!(a.brightness, Brightness::Dark);
+        assert_eq!(a.background, Some(Palette::XTERM.colors[0]));
+        assert_eq!(a.foreground, Some(Palette::XTERM.colors[15]));
+        let a = from_colorfgbg("0;default;15").unwrap();
+        assert_eq!(a.brightness, Brightness::Light);
+        assert_eq!(a.foreground, Some(Palette::XTERM.colors[0]));
+        assert_eq!(from_colorfgbg("7").unwrap().brightness, Brightness::Light);
+        assert_eq!(from_colorfgbg("15;default"), None);
+        assert_eq!(from_colorfgbg("0;16"), None);
+    }
+
+    #[test]
+    fn queried_colors() {
+        let mut tty = Scripted::new(b"\x1b]11;rgb:ffff/ffff/dddd\x1b\\\x1b]10;rgb:0000/0000/0000\x07");
+        let a = detect_with(&mut tty, TIMEOUT).unwrap();
+        assert_eq!(a.brightness, Brightness::Light);
+        assert_eq!(a.background, Some(Rgb::new(255, 255, 221)));
+        assert_eq!(a.foreground, Some(Rgb::new(0, 0, 0)));
+        assert_eq!(tty.written, b"\x1b]11;?\x07\x1b]10;?\x07");
+        // a reply to another request is ignored
+        let mut tty = Scripted::new(b"\x1b]10;rgb:0000/0000/0000\x07");
+        assert_eq!(query_background(&mut tty, TIMEOUT).unwrap(), None);
+    }
+
+    #[test]
+    fn palette() {
+        let mut tty = Scripted::new(b"\x1b]4;1;rgb:cdcd/0000/0000\x07");
+        assert_eq!(query_palette_color(&mut tty, Red, TIMEOUT).unwrap(), Some(Rgb::new(205, 0, 0)));
+        assert_eq!(tty.written, b"\x1b]4;1;?\x07");
+        assert_eq!(query_palette_color(&mut Scripted::new(b""), Default, TIMEOUT).unwrap(), None);
+
+        let mut replies = Vec::new();
+        for i in 0..16 {
+            replies.extend(format!("\x1b]4;{};rgb:{:02x}/00/00\x1b\\", i, i * 16).into_bytes());
+        }
+        let palette = query_palette(&mut Scripted::new(&replies), TIMEOUT).unwrap().unwrap();
+        assert_eq!(palette.colors[15], Rgb::new(240, 0, 0));
+        // entries the terminal does not report keep the xterm values
+        let palette = query_palette(&mut Scripted::new(b"\x1b]4;0;rgb:01/02/03\x07"), TIMEOUT).unwrap().unwrap();
+        assert_eq!(palette.colors[0], Rgb::new(1, 2, 3));
+        assert_eq!(palette.colors[1..], Palette::XTERM.colors[1..]);
+        assert_eq!(query_palette(&mut Scripted::new(b""), TIMEOUT).unwrap(), None);
+    }
+
+    #[test]
+    fn set_and_reset() {
+        let mut tty = Scripted::new(b"");
+        set_palette_color(&mut tty, Red, Rgb::new(0x12, 0xab, 0)).unwrap();
+        set_palette_color(&mut tty, Default, Rgb::new(0, 0, 0)).unwrap();
+        reset_palette(&mut tty, &[]).unwrap();
+        assert_eq!(tty.written, b"\x1b]4;1;rgb:12/ab/00\x07\x1b]104\x07");
+    }
+
+    #[test]
+    fn palette_guard() {
+        let mut tty = Scripted::new(b"");
+        {
+            let mut guard = PaletteGuard::new(&mut tty);
+            guard.set(Red, Rgb::new(255, 0, 0)).unwrap();
+            guard.set(BrightGrey, Rgb::new(1, 2, 3)).unwrap();
+            guard.set(Red, Rgb::new(0, 0, 255)).unwrap();
+            guard.set(Default, Rgb::new(0, 0, 0)).unwrap();
+        }
+        assert_eq!(String::from_utf8(tty.written.clone()).unwrap(),
+                   "\x1b]4;1;rgb:ff/00/00\x07\x1b]4;15;rgb:01/02/03\x07\x1b]4;1;rgb:00/00/ff\x07\
+                    \x1b]104;1;15\x07");
+        tty.written.clear();
+        drop(PaletteGuard::new(&mut tty));
+        assert!(tty.written.is_empty());
+    }
+}
@@ -0,0 +1,92 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Convert colored text to a html page.
+//
+// ```Shell
+// > cargo test --color=always 2>&1 | ansi2html --title tests > tests.html
+// ```
+
+#![crate_name = "ansi2html"]
+
+extern crate colorize;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter};
+use std::process;
+
+use colorize::{Palette, Theme, HtmlWriter};
+use colorize::convert;
+
+const USAGE: &str = "\
+Usage: ansi2html [OPTIONS] [FILE]
+
+Convert colored text read from FILE, or stdin, to a html page on stdout.
+
+Options:
+  -p, --palette PALETTE   xterm, vga, tango or 16 comma separated colors
+                          such as `#000000,#cd0000,...` (default xterm)
+  -t, --theme THEME       dark or light (default dark)
+      --title TITLE       title of the page
+  -h, --help              show this help
+";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("ansi2html: {}", msg);
+    process::exit(2)
+}
+
+fn main() {
+    let (mut palette, mut theme, mut title, mut path) = (Palette::XTERM, String::from("dark"), String::new(), None);
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| fail(&format!("missing value for {}", arg)));
+        match arg.as_str() {
+            "-p" | "--palette" => palette = value().parse().unwrap_or_else(|e: String| fail(&e)),
+            "-t" | "--theme" => theme = value(),
+            "--title" => title = value(),
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return;
+            }
+            "-" => path = None,
+            _ if arg.starts_with('-') => fail(&format!("unknown argument `{}`, see --help", arg)),
+            _ => path = Some(arg.clone())
+        }
+    }
+    let theme = Theme::named(&theme, palette).unwrap_or_else(|| fail(&format!("unknown theme `{}`", theme)));
+    let input: Box<dyn BufRead> = match path {
+        Some(ref path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) => fail(&format!("cannot read {}: {}", path, e))
+        },
+        None => Box::new(BufReader::new(io::stdin()))
+    };
+    let stdout = io::stdout();
+    let result = HtmlWriter::with_title(BufWriter::new(stdout.lock()), theme, &title)
+        .and_then(|mut html| convert::convert(input, &mut html));
+    match result {
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => fail(&e.to_string()),
+        Ok(()) => {}
+    }
+}
@@ -0,0 +1,107 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Remove the escape sequences of colored text.
+//
+// ```Shell
+// > ansi2txt build.log > build.txt
+// ```
+
+#![crate_name = "ansi2txt"]
+
+extern crate colorize;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process;
+
+use colorize::ansi;
+
+const USAGE: &str = "\
+Usage: ansi2txt [FILE]
+
+Write the text read from FILE, or stdin, without its escape sequences.
+Everything else is written unchanged, even bytes that are not UTF-8.
+
+Options:
+  -h, --help   show this help
+";
+
+// Copy the input without its escape sequences, line by line
+fn strip<R: BufRead, W: Write>(mut input: R, out: &mut W) -> io::Result<()> {
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if input.read_until(b'\n', &mut buf)? == 0 {
+            return out.flush();
+        }
+        for chunk in buf.utf8_chunks() {
+            out.write_all(ansi::strip(chunk.valid()).as_bytes())?;
+            out.write_all(chunk.invalid())?;
+        }
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("ansi2txt: {}", msg);
+    process::exit(2)
+}
+
+fn main() {
+    let mut path = None;
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return;
+            }
+            "-" => path = None,
+            _ if arg.starts_with('-') => fail(&format!("unknown argument `{}`, see --help", arg)),
+            _ => path = Some(arg)
+        }
+    }
+    let input: Box<dyn BufRead> = match path {
+        Some(ref path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(e) => fail(&format!("cannot read {}: {}", path, e))
+        },
+        None => Box::new(BufReader::new(io::stdin()))
+    };
+    let stdout = io::stdout();
+    match strip(input, &mut BufWriter::new(stdout.lock())) {
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => fail(&e.to_string()),
+        Ok(()) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strip;
+
+    #[test]
+    fn bytes_are_kept() {
+        let mut out = Vec::new();
+        strip(&b"\x1b[1;31ma\xffb\x1b[0m\r\nc\x1b]0;t\x07d"[..], &mut out).unwrap();
+        assert_eq!(out, b"a\xffb\r\ncd");
+    }
+}
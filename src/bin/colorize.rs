@@ -0,0 +1,399 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Color the lines read on stdin with regex rules.
+//
+// ```Shell
+// > tail -f server.log | colorize --rule 'ERROR=bold red' --rule '\d+ms=cyan'
+// ```
+
+#![crate_name = "colorize"]
+
+extern crate colorize;
+extern crate regex;
+
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process;
+use std::str;
+
+use colorize::Paint;
+use colorize::ansi::{self, Token};
+use colorize::paint::RESET;
+use colorize::term::{self, Stream};
+use regex::Regex;
+
+const USAGE: &str = "\
+Usage: colorize [OPTIONS]
+
+Color the lines read on stdin and write them to stdout.
+
+Options:
+  -r, --rule PATTERN=STYLE   color the matches of a regex, see below
+  -f, --rules FILE           read rules from a file
+  -c, --color WHEN           auto, always or never (default auto)
+  -h, --help                 show this help
+
+A style is made of words such as `bold underline red on blue`, colors may
+also be written `#rrggbb`. A comma separated list of styles colors the
+capture groups: the first style applies to the whole match, the next ones
+to the groups, `default` leaving a group unchanged:
+
+  colorize --rule '(\\w+)=(\\d+)=default,green,cyan'
+
+Rule files use the format of grc, with rules separated by a `-` line:
+
+  # durations
+  regexp=(\\d+)(ms|s)\\b
+  colours=default,bold cyan,cyan
+  count=more
+  -
+  regexp=^ERROR.*
+  colours=bold red
+  count=stop
+
+With `count=once` only the first match of a line is colored, with
+`count=stop` the following rules are skipped on lines the rule matches.
+Later rules are drawn over earlier ones.
+
+Lines that are not valid UTF-8 are written unchanged.
+";
+
+/// How many matches of a rule are colored on a line
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Count {
+    Once,
+    More,
+    Stop
+}
+
+/// A regex and the paints of the match and of its groups
+struct Rule {
+    regex: Regex,
+    paints: Vec<Option<Paint>>,
+    count: Count
+}
+
+impl Rule {
+    fn new(pattern: &str, styles: &str, count: Count) -> Result<Rule, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid pattern `{}`: {}", pattern, e))?;
+        let mut paints = Vec::new();
+        for style in styles.split(',').map(str::trim) {
+            paints.push(match style {
+                "" | "default" | "none" => None,
+                style => Some(style.parse::<Paint>().map_err(|e| format!("{} in `{}`", e, styles))?)
+            });
+        }
+        Ok(Rule { regex, paints, count })
+    }
+
+    /// A `PATTERN=STYLE` rule given on the command line
+    fn parse(arg: &str) -> Result<Rule, String> {
+        match arg.rfind('=') {
+            Some(i) => Rule::new(&arg[..i], &arg[i + 1..], Count::More),
+            None => Err(format!("missing `=STYLE` in rule `{}`", arg))
+        }
+    }
+}
+
+/// Read the rules of a grc style configuration file
+fn read_rules(path: &str) -> Result<Vec<Rule>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+    parse_rules(&text, path)
+}
+
+/// Parse grc style rules, `path` is used in the errors
+fn parse_rules(text: &str, path: &str) -> Result<Vec<Rule>, String> {
+    let mut rules = Vec::new();
+    let (mut pattern, mut styles, mut count) = (None::<String>, String::new(), Count::More);
+    let lines = text.lines().map(str::trim_end).chain(Some("-"));
+    for (n, line) in lines.enumerate() {
+        let error = |msg: String| format!("{}:{}: {}", path, n + 1, msg);
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.bytes().all(|b| b == b'-' || b == b'=') {
+            if let Some(p) = pattern.take() {
+                rules.push(Rule::new(&p, &styles, count).map_err(&error)?);
+            }
+            styles.clear();
+            count = Count::More;
+            continue;
+        }
+        let (key, value) = match line.find('=') {
+            Some(i) => (line[..i].trim(), &line[i + 1..]),
+            None => return Err(error(format!("expected `key=value`, found `{}`", line)))
+        };
+        match key {
+            "regexp" => pattern = Some(String::from(value)),
+            "colours" | "colors" => styles = String::from(value),
+            "count" => count = match value.trim() {
+                "once" => Count::Once,
+                "more" => Count::More,
+                "stop" => Count::Stop,
+                other => return Err(error(format!("unknown count `{}`", other)))
+            },
+            // other grc keys such as `skip` or `replace` are not supported
+            _ => return Err(error(format!("unknown key `{}`", key)))
+        }
+    }
+    Ok(rules)
+}
+
+/// Applies the rules to lines, keeping the escapes already in them
+struct Colorizer {
+    rules: Vec<Rule>,
+    // the colors and styles set by the input, restored after each match
+    active: Paint
+}
+
+impl Colorizer {
+    fn new(rules: Vec<Rule>) -> Colorizer {
+        Colorizer { rules, active: Paint::new() }
+    }
+
+    /// Follow the colors and styles set by a text written unchanged
+    fn skip(&mut self, text: &str) {
+        for token in ansi::tokens(text) {
+            if let Token::Escape(escape) = token {
+                self.active = self.active.apply_sgr(escape);
+            }
+        }
+    }
+
+    /// The paint of each byte of the visible text
+    fn paints(&self, visible: &str) -> Vec<Option<Paint>> {
+        let mut paints = vec![None; visible.len()];
+        for rule in &self.rules {
+            let mut matched = false;
+            for caps in rule.regex.captures_iter(visible) {
+                for (i, paint) in rule.paints.iter().enumerate() {
+                    if let (Some(paint), Some(m)) = (paint, caps.get(i)) {
+                        for p in &mut paints[m.range()] {
+                            *p = Some(*paint);
+                        }
+                    }
+                }
+                matched = true;
+                if rule.count == Count::Once {
+                    break;
+                }
+            }
+            if matched && rule.count == Count::Stop {
+                break;
+            }
+        }
+        paints
+    }
+
+    /// Write a line, without its end of line
+    fn write_line<W: Write>(&mut self, out: &mut W, line: &str) -> io::Result<()> {
+        let paints = self.paints(&ansi::strip(line));
+        let (mut pos, mut current) = (0, None::<Paint>);
+        for token in ansi::tokens(line) {
+            match token {
+                Token::Escape(escape) => {
+                    out.write_all(escape.as_bytes())?;
+                    self.active = self.active.apply_sgr(escape);
+                    if ansi::is_sgr(escape) && ansi::is_reset(escape) {
+                        // the input reset our colors too
+                        if let Some(paint) = current {
+                            write!(out, "{}", paint.prefix())?;
+                        }
+                    }
+                }
+                Token::Text(text) => {
+                    let mut start = 0;
+                    for (i, _) in text.char_indices() {
+                        let paint = paints[pos + i];
+                        if paint == current {
+                            continue;
+                        }
+                        out.write_all(&text.as_bytes()[start..i])?;
+                        start = i;
+                        if current.is_some() {
+                            write!(out, "{}{}", RESET, self.active.sgr())?;
+                        }
+                        if let Some(paint) = paint {
+                            write!(out, "{}", paint.prefix())?;
+                        }
+                        current = paint;
+                    }
+                    out.write_all(&text.as_bytes()[start..])?;
+                    pos += text.len();
+                }
+            }
+        }
+        if current.is_some() {
+            write!(out, "{}{}", RESET, self.active.sgr())?;
+        }
+        Ok(())
+    }
+}
+
+fn run<R: Read, W: Write>(input: R, out: &mut W, mut colorizer: Option<Colorizer>) -> io::Result<()> {
+    let mut input = BufReader::new(input);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if input.read_until(b'\n', &mut buf)? == 0 {
+            return out.flush();
+        }
+        match colorizer {
+            Some(ref mut colorizer) => match str::from_utf8(&buf) {
+                Ok(line) => {
+                    let end = line.trim_end_matches(&['\n', '\r'][..]).len();
+                    colorizer.write_line(out, &line[..end])?;
+                    out.write_all(&line.as_bytes()[end..])?;
+                }
+                Err(_) => {
+                    colorizer.skip(&String::from_utf8_lossy(&buf));
+                    out.write_all(&buf)?;
+                }
+            },
+            None => out.write_all(&buf)?
+        }
+        // flush whenever the input has to wait, for `tail -f`
+        if input.buffer().is_empty() {
+            out.flush()?;
+        }
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("colorize: {}", msg);
+    process::exit(2)
+}
+
+fn main() {
+    let mut rules = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => (String::from(&arg[..i]), Some(String::from(&arg[i + 1..]))),
+            _ => (arg.clone(), None)
+        };
+        let mut value = || inline.clone().or_else(|| args.next())
+            .unwrap_or_else(|| fail(&format!("missing value for {}", flag)));
+        match flag.as_str() {
+            "-r" | "--rule" => rules.push(Rule::parse(&value()).unwrap_or_else(|e| fail(&e))),
+            "-f" | "--rules" => rules.extend(read_rules(&value()).unwrap_or_else(|e| fail(&e))),
+            "-c" | "--color" => term::set_colors_enabled(match value().as_str() {
+                "auto" => None,
+                "always" => Some(true),
+                "never" => Some(false),
+                other => fail(&format!("unknown color mode `{}`", other))
+            }),
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return;
+            }
+            _ => fail(&format!("unknown argument `{}`, see --help", arg))
+        }
+    }
+    let colorizer = if term::colors_enabled(Stream::Stdout) { Some(Colorizer::new(rules)) } else { None };
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    match run(stdin.lock(), &mut stdout.lock(), colorizer) {
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => fail(&e.to_string()),
+        Ok(()) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use colorize::Paint;
+    use colorize::paint::RESET;
+
+    use super::{parse_rules, run, Colorizer, Count, Rule};
+
+    fn colorize(rules: Vec<Rule>, input: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        run(input, &mut out, Some(Colorizer::new(rules))).unwrap();
+        out
+    }
+
+    fn prefix(style: &str) -> String {
+        style.parse::<Paint>().unwrap().prefix()
+    }
+
+    #[test]
+    fn rules_file() {
+        let rules = parse_rules("# durations\nregexp=(\\d+)(ms|s)\\b\ncolours=default,bold cyan,cyan\n\
+                                 count=once\n-\nregexp=^ERROR.*\ncolors=bold red\ncount=stop\n", "f").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].regex.as_str(), "(\\d+)(ms|s)\\b");
+        assert_eq!(rules[0].paints, [None, Some("bold cyan".parse().unwrap()), Some("cyan".parse().unwrap())]);
+        assert_eq!(rules[0].count, Count::Once);
+        assert_eq!(rules[1].count, Count::Stop);
+        assert!(parse_rules("", "f").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rules_file_errors() {
+        assert_eq!(parse_rules("regexp=a\nskip=yes\n", "f").err().unwrap(), "f:2: unknown key `skip`");
+        assert_eq!(parse_rules("regexp=a\ncount=twice\n", "f").err().unwrap(), "f:2: unknown count `twice`");
+        assert_eq!(parse_rules("oops\n", "f").err().unwrap(), "f:1: expected `key=value`, found `oops`");
+        assert!(parse_rules("regexp=(\ncolours=red\n", "f").err().unwrap().starts_with("f:3: invalid pattern"));
+        assert!(parse_rules("regexp=a\ncolours=purple\n", "f").is_err());
+    }
+
+    #[test]
+    fn matches() {
+        let out = colorize(vec![Rule::parse("\\d+=green").unwrap()], b"took 12ms\n");
+        let expected = format!("took {}12{}ms\n", prefix("green"), RESET);
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn groups() {
+        let out = colorize(vec![Rule::parse("(\\w+)=(\\d+)=default,cyan,default").unwrap()], b"a=1\r\n");
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{}a{}=1\r\n", prefix("cyan"), RESET));
+    }
+
+    #[test]
+    fn input_colors_are_restored() {
+        let out = colorize(vec![Rule::parse("x=green").unwrap()], b"\x1b[1m\x1b[34ma x b\x1b[0m x\n");
+        let expected = format!("\x1b[1m\x1b[34ma {green}x{RESET}\x1b[34;1m b\x1b[0m {green}x{RESET}\n",
+                               green = prefix("green"), RESET = RESET);
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn active_state_stays_small() {
+        let mut colorizer = Colorizer::new(Vec::new());
+        for _ in 0..1000 {
+            colorizer.skip("\x1b[31m\x1b[1m\x1b[0m\x1b[32m");
+        }
+        assert_eq!(colorizer.active.sgr().as_str(), "\x1b[32m");
+    }
+
+    #[test]
+    fn invalid_utf8_is_unchanged() {
+        let input = b"bad \xff 12\n\x1b[31mok 3\n";
+        let out = colorize(vec![Rule::parse("\\d+=green").unwrap()], input);
+        let mut expected = b"bad \xff 12\n\x1b[31mok ".to_vec();
+        expected.extend(format!("{}3{}\x1b[31m\n", prefix("green"), RESET).into_bytes());
+        assert_eq!(out, expected);
+    }
+}
@@ -49,17 +49,125 @@
 // ```
 
 #![crate_name = "colorize"]
-#![crate_type = "dylib"]
 #![crate_type = "rlib"]
+#![cfg_attr(not(feature = "std"), no_std)]
 
 use Color::*;
 use BgColor::*;
 use Style::*;
 
-use std::mem;
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
+extern crate unicode_segmentation;
+extern crate unicode_width;
+#[cfg(all(unix, feature = "std"))]
+extern crate libc;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(feature = "tracing")]
+extern crate tracing_core;
+#[cfg(feature = "tracing")]
+extern crate tracing_subscriber;
+
+#[cfg(feature = "std")]
+use core::mem;
+
+use alloc::string::String;
+
+pub use ansi::{strip, visible_width};
+#[cfg(feature = "std")]
+pub use rgb::{Rgb, Palette, Oklab, Oklch, ColorDepth, color_depth, set_color_depth, mix};
+#[cfg(feature = "std")]
+pub use progress::ProgressBar;
+#[cfg(feature = "std")]
+pub use spinner::{Spinner, Frames};
+#[cfg(feature = "std")]
+pub use table::{Table, Align, Border};
+#[cfg(feature = "std")]
+pub use wrap::{wrap, truncate};
+#[cfg(feature = "std")]
+pub use styled::{Styled, Padding};
+#[cfg(feature = "std")]
+pub use term::{Stream, colors_enabled, set_colors_enabled};
+#[cfg(feature = "std")]
+pub use diff::Diff;
+pub use paint::{Paint, Sgr, Ink};
+pub use config::ParseError;
+pub use text::StyledString;
+#[cfg(feature = "std")]
+pub use json::{JsonPalette, JsonPrinter, highlight_json};
+#[cfg(feature = "std")]
+pub use debug::{debug, ColoredDebug, DebugPalette};
+#[cfg(feature = "std")]
+pub use appearance::{Appearance, Brightness, PaletteGuard};
+#[cfg(feature = "std")]
+pub use osc::{Osc, TitleGuard};
+#[cfg(feature = "std")]
+pub use contrast::{contrast_ratio, readable_on, set_min_contrast};
+#[cfg(feature = "std")]
+pub use cvd::{Deficiency, Status, set_cvd_safe};
+#[cfg(feature = "std")]
+pub use convert::{Theme, HtmlWriter, SvgWriter, TextWriter};
+#[cfg(feature = "std")]
+pub use terminfo::{Terminfo, Capabilities};
+#[cfg(feature = "std")]
+pub use mux::Multiplexer;
+
+pub mod ansi;
+#[cfg(feature = "std")]
+mod gradient;
+#[cfg(feature = "std")]
+pub mod rgb;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "std")]
+pub mod spinner;
+#[cfg(feature = "std")]
+pub mod table;
+#[cfg(feature = "std")]
+pub mod wrap;
+#[cfg(feature = "std")]
+pub mod styled;
+#[cfg(feature = "std")]
+pub mod term;
+#[cfg(feature = "std")]
+pub mod diff;
+pub mod paint;
+pub mod config;
+pub mod text;
+#[cfg(feature = "std")]
+pub mod json;
+#[cfg(feature = "std")]
+pub mod debug;
+#[cfg(feature = "std")]
+pub mod tty;
+#[cfg(feature = "std")]
+pub mod appearance;
+#[cfg(feature = "std")]
+pub mod osc;
+#[cfg(feature = "std")]
+pub mod contrast;
+#[cfg(feature = "std")]
+pub mod cvd;
+#[cfg(feature = "std")]
+pub mod convert;
+#[cfg(feature = "std")]
+pub mod terminfo;
+#[cfg(feature = "std")]
+pub mod mux;
+#[cfg(feature = "log")]
+pub mod logger;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod time;
 
 /// Ansi color to set the global foreground / background color
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Color {
     Black = 30,
     Red = 31,
@@ -80,7 +188,7 @@ pub enum Color {
     BrightGrey = 97
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum BgColor {
     Blackb = 40,
     Redb = 41,
@@ -101,7 +209,7 @@ pub enum BgColor {
     BrightGreyb = 107
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Style {
     Underscore = 4,
     Bold = 1,
@@ -125,6 +233,7 @@ impl internal::TermAttrib for Style {
     fn to_int(&self) -> i32 { *self as i32 }
 }
 
+#[cfg(feature = "std")]
 impl BgColor {
     fn from_fg(color: Color) -> BgColor {
         unsafe { mem::transmute(color as i8 + 10) }
@@ -132,33 +241,49 @@ impl BgColor {
 }
 
 mod internal {
+    use alloc::string::String;
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+    #[cfg(feature = "std")]
     use super::{Color, BgColor};
+    #[cfg(feature = "std")]
     use std::cell::RefCell;
 
     static DEFAULT_FG: i32 = 39;
     static DEFAULT_BG: i32 = 49;
+    #[cfg(feature = "std")]
     thread_local!(static GLOB_COLOR: RefCell<GlobalColor> = RefCell::new(GlobalColor {fg: DEFAULT_FG, bg: DEFAULT_BG}));
 
     pub trait TermAttrib {
         fn to_int(&self) -> i32;
     }
 
+    #[cfg(feature = "std")]
     #[derive(Clone)]
     pub struct GlobalColor {
         fg: i32,
         bg: i32
     }
 
+    #[cfg(feature = "std")]
     impl Drop for GlobalColor {
         fn drop(&mut self) {
             print!("\x1b[0;{};{}m", DEFAULT_FG, DEFAULT_BG)
         }
     }
 
-    fn get_glob() -> (i32, i32) {
+    #[cfg(feature = "std")]
+    pub fn get_glob() -> (i32, i32) {
         GLOB_COLOR.with (|cell| {let g = cell.borrow(); (g.fg, g.bg)})
     }
 
+    // Without std there are no global colors
+    #[cfg(not(feature = "std"))]
+    pub fn get_glob() -> (i32, i32) {
+        (DEFAULT_FG, DEFAULT_BG)
+    }
+
+    #[cfg(feature = "std")]
     pub fn global_color(fg_color: Option<Color>, bg_color: Option<BgColor>) {
         GLOB_COLOR.with (|cell| {
             let mut g = cell.borrow_mut();
@@ -173,7 +298,25 @@ mod internal {
         })
     }
 
-    pub fn pack<T: TermAttrib>(attrib: T, mut text: String) -> String {
+    // The code written for an attribute, none if the terminal is not able to display it
+    #[cfg(feature = "std")]
+    pub fn code<T: TermAttrib>(attrib: T) -> Option<String> {
+        ::terminfo::filter(attrib.to_int()).map(::cvd::code)
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub fn code<T: TermAttrib>(attrib: T) -> Option<String> {
+        Some(attrib.to_int().to_string())
+    }
+
+    pub fn pack<T: TermAttrib>(attrib: T, text: String) -> String {
+        match code(attrib) {
+            Some(code) => pack_code(code, text),
+            None => text
+        }
+    }
+
+    pub fn pack_code(code: String, mut text: String) -> String {
         if text.as_str().starts_with("\x1b[") {
             unsafe {
                 text.as_mut_vec().remove(0);
@@ -181,36 +324,45 @@ mod internal {
             }
             let tmp = text;
             text = String::from("\x1b[");
-            text.push_str(format!("{};", attrib.to_int()).as_str());
+            text.push_str(format!("{};", code).as_str());
             text.push_str(tmp.as_str());
         } else {
             let tmp = text;
-            text = format!("\x1b[{}m", attrib.to_int());
+            text = format!("\x1b[{}m", code);
             text.push_str(tmp.as_str());
             let (fg, bg) = get_glob();
             text.push_str(format!("\x1b[0;{};{}m", fg, bg).as_str());
         }
+        #[cfg(feature = "std")]
+        ::contrast::check(&text);
         text
     }
 }
 
 /// Set a custom global foreground color
+#[cfg(feature = "std")]
 pub fn global_fg(color: Color) {
     internal::global_color(Some(color), None)
 }
 
 /// Set a custom global background color
+#[cfg(feature = "std")]
 pub fn global_bg(color: Color) {
     internal::global_color(None, Some(BgColor::from_fg(color)))
 }
 
 /// Reset the background and foreground color to the defaults colors
+#[cfg(feature = "std")]
 pub fn reset() {
     internal::global_color(Some(Default), Some(Defaultb))
 }
 
 /// Methods extension to colorize the text contained in a string
 /// using a simple mathod call
+///
+/// The methods return a `String` holding escapes, which a format width
+/// counts: `format!("{:>10}", "ok".green())` is padded too little. Call
+/// `styled` on the result to pad the visible text.
 pub trait AnsiColor {
     /// Foreground black
     fn black(self) -> String;
@@ -298,6 +450,26 @@ pub trait AnsiColor {
     fn italic(self) -> String;
     /// Crossed out
     fn crossedout(self) -> String;
+
+    /// Rgb foreground, rendered at the terminal color depth
+    #[cfg(feature = "std")]
+    fn rgb(self, color: Rgb) -> String;
+    /// Rgb background, rendered at the terminal color depth
+    #[cfg(feature = "std")]
+    fn rgbb(self, color: Rgb) -> String;
+
+    /// Foreground gradient going through the given colors
+    #[cfg(feature = "std")]
+    fn gradient(self, stops: &[Rgb]) -> String;
+    /// Background gradient going through the given colors
+    #[cfg(feature = "std")]
+    fn gradientb(self, stops: &[Rgb]) -> String;
+
+    /// Make format width and precision apply to the visible text
+    ///
+    /// `format!("{:>10}", "ok".green().styled())` is padded to 10 columns.
+    #[cfg(feature = "std")]
+    fn styled(self) -> Styled;
 }
 
 impl AnsiColor for String {
@@ -348,6 +520,21 @@ impl AnsiColor for String {
     fn faint(self) -> String { internal::pack(Faint, self) }
     fn italic(self) -> String { internal::pack(Italic, self) }
     fn crossedout(self) -> String { internal::pack(CrossedOut, self) }
+
+    // rgb colors
+    #[cfg(feature = "std")]
+    fn rgb(self, color: Rgb) -> String { internal::pack_code(rgb::fg_code(color, color_depth()), self) }
+    #[cfg(feature = "std")]
+    fn rgbb(self, color: Rgb) -> String { internal::pack_code(rgb::bg_code(color, color_depth()), self) }
+
+    // gradients
+    #[cfg(feature = "std")]
+    fn gradient(self, stops: &[Rgb]) -> String { gradient::paint(self, stops, false) }
+    #[cfg(feature = "std")]
+    fn gradientb(self, stops: &[Rgb]) -> String { gradient::paint(self, stops, true) }
+
+    #[cfg(feature = "std")]
+    fn styled(self) -> Styled { Styled::new(self) }
 }
 
 impl AnsiColor for &'static str {
@@ -398,4 +585,19 @@ impl AnsiColor for &'static str {
     fn faint(self) -> String { String::from(self).faint() }
     fn italic(self) -> String { String::from(self).italic() }
     fn crossedout(self) -> String { String::from(self).crossedout() }
+
+    // rgb colors
+    #[cfg(feature = "std")]
+    fn rgb(self, color: Rgb) -> String { String::from(self).rgb(color) }
+    #[cfg(feature = "std")]
+    fn rgbb(self, color: Rgb) -> String { String::from(self).rgbb(color) }
+
+    // gradients
+    #[cfg(feature = "std")]
+    fn gradient(self, stops: &[Rgb]) -> String { String::from(self).gradient(stops) }
+    #[cfg(feature = "std")]
+    fn gradientb(self, stops: &[Rgb]) -> String { String::from(self).gradientb(stops) }
+
+    #[cfg(feature = "std")]
+    fn styled(self) -> Styled { String::from(self).styled() }
 }
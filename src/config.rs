@@ -0,0 +1,487 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Text forms of colors and styles, for configuration files.
+//
+// Colors are written `red` or `bright-red`, styles `bold` or `underline`,
+// and a paint `bold underline red on blue`, where colors may also be
+// `#ff0000`. With the `serde` feature these forms are used to serialize
+// them, and a paint can also be read from a map:
+//
+// ```Rust
+// { "fg": "red", "bg": "#000080", "styles": ["bold", "underline"] }
+// ```
+
+use core::fmt;
+use core::str::FromStr;
+
+use alloc::string::String;
+
+use {Color, BgColor, Style};
+use Color::*;
+use BgColor::*;
+use Style::*;
+use paint::{Paint, Ink};
+
+/// An unknown color, style or paint
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    word: String
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown color or style `{}`", self.word)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for ParseError {}
+
+fn error(word: &str) -> ParseError {
+    ParseError { word: String::from(word) }
+}
+
+const COLORS: [(Color, &str); 17] = [
+    (Black, "black"), (Red, "red"), (Green, "green"), (Yellow, "yellow"),
+    (Blue, "blue"), (Magenta, "magenta"), (Cyan, "cyan"), (Grey, "grey"),
+    (Default, "default"), (BrightBlack, "bright-black"), (BrightRed, "bright-red"),
+    (BrightGreen, "bright-green"), (BrightYellow, "bright-yellow"), (BrightBlue, "bright-blue"),
+    (BrightMagenta, "bright-magenta"), (BrightCyan, "bright-cyan"), (BrightGrey, "bright-grey")
+];
+
+const STYLES: [(Style, &str); 8] = [
+    (Bold, "bold"), (Faint, "faint"), (Italic, "italic"), (Underscore, "underline"),
+    (Blink, "blink"), (Reverse, "reverse"), (Concealed, "concealed"), (CrossedOut, "crossed-out")
+];
+
+// Compare ignoring case, `-`, `_` and spaces, so `Bright_Red` is `bright-red`
+fn same(word: &str, name: &str) -> bool {
+    let mut a = word.chars().filter(|c| !matches!(c, '-' | '_' | ' ')).map(|c| c.to_ascii_lowercase());
+    let mut b = name.chars().filter(|&c| c != '-');
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (x, y) if x != y => return false,
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = COLORS.iter().find(|c| c.0 == *self).map_or("default", |c| c.1);
+        f.write_str(name)
+    }
+}
+
+// Other names of the colors and the styles
+const COLOR_ALIASES: [(&str, &str); 4] = [
+    ("gray", "grey"), ("white", "grey"), ("bright-gray", "bright-grey"), ("bright-white", "bright-grey")
+];
+
+const STYLE_ALIASES: [(&str, &str); 5] = [
+    ("underlined", "underline"), ("underscore", "underline"), ("dim", "faint"),
+    ("hidden", "concealed"), ("strikethrough", "crossed-out")
+];
+
+// The name a word stands for, compared like `same`
+fn unalias<'a>(word: &'a str, aliases: &[(&str, &'static str)]) -> &'a str {
+    aliases.iter().find(|a| same(word, a.0)).map_or(word, |a| a.1)
+}
+
+impl FromStr for Color {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Color, ParseError> {
+        let s = s.trim();
+        let name = unalias(s, &COLOR_ALIASES);
+        COLORS.iter().find(|c| same(name, c.1)).map(|c| c.0).ok_or_else(|| error(s))
+    }
+}
+
+impl BgColor {
+    fn to_fg(self) -> Color {
+        COLORS.iter().map(|c| c.0).find(|&c| c as i32 + 10 == self as i32).unwrap_or(Default)
+    }
+}
+
+const BG_COLORS: [BgColor; 17] = [
+    Blackb, Redb, Greenb, Yellowb, Blueb, Magentab, Cyanb, Greyb, Defaultb,
+    BrightBlackb, BrightRedb, BrightGreenb, BrightYellowb, BrightBlueb,
+    BrightMagentab, BrightCyanb, BrightGreyb
+];
+
+impl fmt::Display for BgColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_fg().fmt(f)
+    }
+}
+
+impl FromStr for BgColor {
+    type Err = ParseError;
+
+    /// Background colors have the names of the foreground ones
+    fn from_str(s: &str) -> Result<BgColor, ParseError> {
+        let fg = s.parse::<Color>()?;
+        Ok(BG_COLORS.iter().cloned().find(|&c| c as i32 == fg as i32 + 10).unwrap_or(Defaultb))
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = STYLES.iter().find(|s| s.0 == *self).map_or("", |s| s.1);
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Style {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Style, ParseError> {
+        let s = s.trim();
+        let name = unalias(s, &STYLE_ALIASES);
+        STYLES.iter().find(|st| same(name, st.1)).map(|st| st.0).ok_or_else(|| error(s))
+    }
+}
+
+fn parse_hex(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let c = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((c(0)?, c(2)?, c(4)?))
+}
+
+/// Parse a color name or `#rrggbb`
+pub fn parse_ink(s: &str) -> Result<Ink<Color>, ParseError> {
+    match parse_hex(s.trim()) {
+        Some((r, g, b)) => Ok(Ink::Rgb(r, g, b)),
+        None => s.parse().map(Ink::Ansi)
+    }
+}
+
+fn fmt_ink<C: fmt::Display>(ink: &Ink<C>, f: &mut fmt::Formatter) -> fmt::Result {
+    match *ink {
+        Ink::Ansi(ref c) => c.fmt(f),
+        Ink::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+impl Paint {
+    /// Set the foreground from a parsed color
+    pub fn fg_ink(self, ink: Ink<Color>) -> Paint {
+        match ink {
+            Ink::Ansi(c) => self.fg(c),
+            Ink::Rgb(r, g, b) => self.fg_rgb(r, g, b)
+        }
+    }
+
+    /// Set the background from a parsed color
+    pub fn bg_ink(self, ink: Ink<Color>) -> Paint {
+        match ink {
+            Ink::Ansi(c) => self.bg(BG_COLORS.iter().cloned().find(|&b| b.to_fg() == c).unwrap_or(Defaultb)),
+            Ink::Rgb(r, g, b) => self.bg_rgb(r, g, b)
+        }
+    }
+}
+
+/// Styles, then the foreground, then `on` and the background
+impl fmt::Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        let mut sep = |f: &mut fmt::Formatter| {
+            let r = if first { Ok(()) } else { f.write_str(" ") };
+            first = false;
+            r
+        };
+        for &(style, name) in STYLES.iter() {
+            if self.has_style(style) {
+                sep(f)?;
+                f.write_str(name)?;
+            }
+        }
+        if let Some(fg) = self.foreground() {
+            sep(f)?;
+            fmt_ink(&fg, f)?;
+        }
+        if let Some(bg) = self.background() {
+            sep(f)?;
+            f.write_str("on ")?;
+            fmt_ink(&bg, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Paint {
+    type Err = ParseError;
+
+    /// Parse words such as `bold underline red on blue`, in any order
+    fn from_str(s: &str) -> Result<Paint, ParseError> {
+        let mut paint = Paint::new();
+        let mut words = s.split_whitespace();
+        while let Some(word) = words.next() {
+            if word == "on" {
+                let bg = words.next().ok_or_else(|| error(word))?;
+                paint = paint.bg_ink(parse_ink(bg)?);
+            } else if let Ok(style) = word.parse::<Style>() {
+                paint = paint.style(style);
+            } else {
+                paint = paint.fg_ink(parse_ink(word)?);
+            }
+        }
+        Ok(paint)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use core::fmt;
+    use core::marker::PhantomData;
+    use core::str::FromStr;
+
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+
+    use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+    use serde::ser::{Serialize, Serializer, SerializeMap};
+
+    use {Color, BgColor, Style};
+    use paint::{Paint, Ink};
+    use super::{parse_ink, STYLES};
+
+    // The keys of a paint map, a style may also be any of its other names
+    const FIELDS: &[&str] = &[
+        "fg", "foreground", "bg", "background", "styles",
+        "bold", "faint", "italic", "underline", "blink", "reverse", "concealed", "crossed-out"
+    ];
+
+    // Deserialize any type from its text form
+    struct TextVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: FromStr> Visitor<'de> for TextVisitor<T> where T::Err: fmt::Display {
+        type Value = T;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a color or a style")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<T, E> {
+            s.parse().map_err(E::custom)
+        }
+    }
+
+    macro_rules! text_serde {
+        ($($t:ty),*) => {$(
+            impl Serialize for $t {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $t {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$t, D::Error> {
+                    deserializer.deserialize_str(TextVisitor(PhantomData))
+                }
+            }
+        )*}
+    }
+
+    text_serde!(Color, BgColor, Style);
+
+    impl Serialize for Paint {
+        /// A paint is written in its text form
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    struct PaintVisitor;
+
+    impl<'de> Visitor<'de> for PaintVisitor {
+        type Value = Paint;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a style such as `bold red on blue`, or a map with fg, bg and styles")
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<Paint, E> {
+            s.parse().map_err(E::custom)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Paint, A::Error> {
+            let mut paint = Paint::new();
+            while let Some(key) = map.next_key::<String>()? {
+                match key.as_str() {
+                    "fg" | "foreground" => {
+                        paint = paint.fg_ink(parse_ink(&map.next_value::<String>()?).map_err(de::Error::custom)?)
+                    }
+                    "bg" | "background" => {
+                        paint = paint.bg_ink(parse_ink(&map.next_value::<String>()?).map_err(de::Error::custom)?)
+                    }
+                    "styles" => {
+                        for style in map.next_value::<Vec<Style>>()? {
+                            paint = paint.style(style);
+                        }
+                    }
+                    // a single style set to true or false
+                    other => {
+                        let style = other.parse::<Style>().map_err(|_| {
+                            de::Error::unknown_field(other, FIELDS)
+                        })?;
+                        if map.next_value::<bool>()? {
+                            paint = paint.style(style);
+                        }
+                    }
+                }
+            }
+            Ok(paint)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Paint {
+        /// A paint is read from its text form or from a map
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Paint, D::Error> {
+            deserializer.deserialize_any(PaintVisitor)
+        }
+    }
+
+    fn ink_string<C: fmt::Display>(ink: Ink<C>) -> String {
+        match ink {
+            Ink::Ansi(c) => c.to_string(),
+            Ink::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b)
+        }
+    }
+
+    /// Write a paint as a map, for `#[serde(serialize_with = "...")]`
+    pub fn serialize_map<S: Serializer>(paint: &Paint, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(fg) = paint.foreground() {
+            map.serialize_entry("fg", &ink_string(fg))?;
+        }
+        if let Some(bg) = paint.background() {
+            map.serialize_entry("bg", &ink_string(bg))?;
+        }
+        let styles: Vec<Style> = STYLES.iter().map(|s| s.0).filter(|&s| paint.has_style(s)).collect();
+        if !styles.is_empty() {
+            map.serialize_entry("styles", &styles)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use self::serde_impls::serialize_map;
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use {Color, BgColor, Style};
+    use Color::{BrightGrey, BrightRed, Grey, Red};
+    use BgColor::Blueb;
+    use Style::{CrossedOut, Faint, Underscore};
+    use paint::Paint;
+    use super::{BG_COLORS, COLORS, STYLES};
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!("red".parse::<Color>(), Ok(Red));
+        assert_eq!(" Bright_Red ".parse::<Color>(), Ok(BrightRed));
+        assert_eq!("GRAY".parse::<Color>(), Ok(Grey));
+        assert_eq!("White".parse::<Color>(), Ok(Grey));
+        assert_eq!("Bright White".parse::<Color>(), Ok(BrightGrey));
+        assert_eq!("blue".parse::<BgColor>(), Ok(Blueb));
+        assert_eq!("purple".parse::<Color>().unwrap_err().to_string(), "unknown color or style `purple`");
+    }
+
+    #[test]
+    fn parse_styles() {
+        assert_eq!("Underlined".parse::<Style>(), Ok(Underscore));
+        assert_eq!("DIM".parse::<Style>(), Ok(Faint));
+        assert_eq!("strike_through".parse::<Style>(), Ok(CrossedOut));
+        assert_eq!("crossedout".parse::<Style>(), Ok(CrossedOut));
+        assert!("loud".parse::<Style>().is_err());
+    }
+
+    #[test]
+    fn parse_paints() {
+        assert_eq!("bold underline red on blue".parse::<Paint>(),
+                   Ok(Paint::new().bold().underlined().fg(Red).bg(Blueb)));
+        assert_eq!("#ff0000 on #000080".parse::<Paint>(), Ok(Paint::new().fg_rgb(255, 0, 0).bg_rgb(0, 0, 128)));
+        assert_eq!("".parse::<Paint>(), Ok(Paint::new()));
+        assert!("red on".parse::<Paint>().is_err());
+        assert!("red on #12345".parse::<Paint>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(BrightRed.to_string(), "bright-red");
+        assert_eq!(Blueb.to_string(), "blue");
+        assert_eq!(CrossedOut.to_string(), "crossed-out");
+        assert_eq!(Paint::new().fg(Red).bg(Blueb).underlined().bold().to_string(), "bold underline red on blue");
+        assert_eq!(Paint::new().fg_rgb(255, 0, 0).bg_rgb(0, 0, 128).to_string(), "#ff0000 on #000080");
+        assert_eq!(Paint::new().to_string(), "");
+    }
+
+    #[test]
+    fn round_trips() {
+        for &(color, _) in COLORS.iter() {
+            assert_eq!(color.to_string().parse::<Color>(), Ok(color));
+        }
+        for &color in BG_COLORS.iter() {
+            assert_eq!(color.to_string().parse::<BgColor>(), Ok(color));
+        }
+        for &(style, _) in STYLES.iter() {
+            assert_eq!(style.to_string().parse::<Style>(), Ok(style));
+        }
+        let paint = Paint::new().italic().fg(BrightRed).bg_rgb(1, 2, 3);
+        assert_eq!(paint.to_string().parse::<Paint>(), Ok(paint));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        extern crate serde_json;
+
+        use alloc::vec::Vec;
+
+        use super::serialize_map;
+
+        let paint = Paint::new().bold().underlined().fg(Red).bg_rgb(0, 0, 128);
+        assert_eq!(serde_json::to_string(&Red).unwrap(), "\"red\"");
+        assert_eq!(serde_json::from_str::<BgColor>("\"Bright_Gray\"").unwrap(), BgColor::BrightGreyb);
+        assert_eq!(serde_json::to_string(&paint).unwrap(), "\"bold underline red on #000080\"");
+        assert_eq!(serde_json::from_str::<Paint>(&serde_json::to_string(&paint).unwrap()).unwrap(), paint);
+
+        let mut map = Vec::new();
+        serialize_map(&paint, &mut serde_json::Serializer::new(&mut map)).unwrap();
+        assert_eq!(map, br##"{"fg":"red","bg":"#000080","styles":["bold","underline"]}"##);
+        assert_eq!(serde_json::from_slice::<Paint>(&map).unwrap(), paint);
+
+        let map = r##"{"foreground": "red", "background": "#000080", "Bold": true, "underlined": true, "dim": false}"##;
+        assert_eq!(serde_json::from_str::<Paint>(map).unwrap(), paint);
+        let error = serde_json::from_str::<Paint>(r#"{"color": "red"}"#).unwrap_err().to_string();
+        assert!(error.contains("unknown field `color`") && error.contains("`crossed-out`"));
+    }
+}
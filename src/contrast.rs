@@ -0,0 +1,167 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Contrast ratios as defined by WCAG 2, and readable colors.
+//
+// ```Rust
+// let fg = colorize::contrast::readable_on(Yellowb, &[Black, BrightGrey]);
+// colorize::contrast::set_min_contrast(Some(4.5));
+// ```
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use {Color, BgColor};
+use Color::{Black, BrightGrey};
+use ansi::{self, SgrParam};
+use internal;
+use rgb::{self, Rgb, Palette};
+
+/// Relative luminance of a color, from 0 for black to 1 for white
+pub fn luminance(rgb: Rgb) -> f64 {
+    0.2126 * rgb::to_linear(rgb.r) + 0.7152 * rgb::to_linear(rgb.g) + 0.0722 * rgb::to_linear(rgb.b)
+}
+
+/// Contrast ratio between two colors, from 1 to 21
+///
+/// WCAG asks for at least 4.5 for text, 3 for large text.
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+impl Palette {
+    /// Contrast ratio between a foreground and a background color
+    pub fn contrast(&self, fg: Color, bg: BgColor) -> f64 {
+        contrast_ratio(self.resolve(fg), self.resolve_bg(bg))
+    }
+
+    /// The candidate with the best contrast on a background
+    ///
+    /// Without candidates it picks between `Black` and `BrightGrey`.
+    pub fn readable_on(&self, bg: BgColor, candidates: &[Color]) -> Color {
+        let candidates = if candidates.is_empty() { &[Black, BrightGrey][..] } else { candidates };
+        let mut best = candidates[0];
+        for &c in candidates {
+            if self.contrast(c, bg) > self.contrast(best, bg) {
+                best = c;
+            }
+        }
+        best
+    }
+}
+
+/// Contrast ratio between two colors of the xterm palette
+pub fn contrast(fg: Color, bg: BgColor) -> f64 {
+    Palette::XTERM.contrast(fg, bg)
+}
+
+/// The candidate with the best contrast on a background, in the xterm palette
+pub fn readable_on(bg: BgColor, candidates: &[Color]) -> Color {
+    Palette::XTERM.readable_on(bg, candidates)
+}
+
+// The minimum ratio as f64 bits, 0 when disabled
+static MIN_CONTRAST: AtomicU64 = AtomicU64::new(0);
+static WARNED: Mutex<Option<HashSet<(Rgb, Rgb)>>> = Mutex::new(None);
+
+/// Warn on stderr when a styled text has a contrast below `ratio`, `None` to stop
+///
+/// Colors are resolved through the xterm palette, each pair of colors is
+/// reported once. Meant to be enabled while developing.
+pub fn set_min_contrast(ratio: Option<f64>) {
+    MIN_CONTRAST.store(ratio.map_or(0, f64::to_bits), Ordering::Relaxed)
+}
+
+// Called on each styled text, warns if its colors are hard to read
+pub fn check(text: &str) {
+    let min = MIN_CONTRAST.load(Ordering::Relaxed);
+    if min == 0 {
+        return;
+    }
+    let (fg, bg) = match colors(text) {
+        Some(colors) => colors,
+        None => return
+    };
+    let ratio = contrast_ratio(fg, bg);
+    if ratio >= f64::from_bits(min) {
+        return;
+    }
+    let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+    if warned.get_or_insert_with(HashSet::new).insert((fg, bg)) {
+        eprintln!("colorize: contrast {:.2}:1 of {:?} on {:?} is too low in {:?}", ratio, fg, bg, ansi::strip(text));
+    }
+}
+
+// The foreground and background colors set by the escape starting a text
+fn colors(text: &str) -> Option<(Rgb, Rgb)> {
+    let params = text.strip_prefix("\x1b[").and_then(|t| t.split('m').next())?;
+    let (glob_fg, glob_bg) = internal::get_glob();
+    let (mut fg, mut bg) = (None, None);
+    for param in ansi::sgr_params(&format!("{};{};{}", glob_fg, glob_bg, params)) {
+        match param {
+            SgrParam::Reset => {
+                fg = None;
+                bg = None;
+            }
+            SgrParam::Fg(ink) => fg = ink.map(|ink| Palette::XTERM.resolve_ink(ink)),
+            SgrParam::Bg(ink) => bg = ink.map(|ink| Palette::XTERM.resolve_ink(ink)),
+            _ => {}
+        }
+    }
+    Some((fg?, bg?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{colors, contrast, contrast_ratio, readable_on};
+    use BgColor::{Blackb, Yellowb};
+    use Color::{Black, Blue, BrightGrey, Yellow};
+    use rgb::Rgb;
+
+    const BLACK: Rgb = Rgb { r: 0, g: 0, b: 0 };
+    const WHITE: Rgb = Rgb { r: 255, g: 255, b: 255 };
+
+    #[test]
+    fn ratios() {
+        assert!((contrast_ratio(BLACK, WHITE) - 21.0).abs() < 1e-9);
+        assert!((contrast_ratio(WHITE, BLACK) - 21.0).abs() < 1e-9);
+        assert_eq!(contrast_ratio(Rgb::new(12, 34, 56), Rgb::new(12, 34, 56)), 1.0);
+        assert!(contrast(Yellow, Yellowb) < 1.0 + 1e-9);
+    }
+
+    #[test]
+    fn readable() {
+        assert_eq!(readable_on(Yellowb, &[]), Black);
+        assert_eq!(readable_on(Blackb, &[]), BrightGrey);
+        assert_eq!(readable_on(Blackb, &[Blue, Yellow]), Yellow);
+    }
+
+    #[test]
+    fn escape_colors() {
+        assert_eq!(colors("\x1b[38;5;231;48;2;0;0;0mx"), Some((WHITE, BLACK)));
+        assert_eq!(colors("\x1b[48;2;1;2;3;30mx"), Some((BLACK, Rgb::new(1, 2, 3))));
+        assert_eq!(colors("\x1b[1mx"), None);
+        assert_eq!(colors("\x1b[31;0;44mx"), None);
+        assert_eq!(colors("x"), None);
+    }
+}
@@ -0,0 +1,454 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Conversion of colored text to html, svg or plain text.
+//
+// ```Rust
+// let mut html = HtmlWriter::new(io::stdout(), Theme::dark(Palette::XTERM))?;
+// convert::convert(io::stdin().lock(), &mut html)?;
+// ```
+
+use std::fmt::Write as FmtWrite;
+use std::io::{self, BufRead, Write};
+
+use unicode_width::UnicodeWidthChar;
+
+use Style;
+use ansi::{self, SgrParam, Token};
+use rgb::{Rgb, Palette};
+
+/// The colors and styles set by the escape sequences read so far
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Attrs {
+    /// Foreground color, `None` for the default one
+    pub fg: Option<Rgb>,
+    /// Background color, `None` for the default one
+    pub bg: Option<Rgb>,
+    pub bold: bool,
+    pub faint: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub blink: bool,
+    pub reverse: bool,
+    pub concealed: bool,
+    pub crossedout: bool
+}
+
+impl Attrs {
+    /// Update the attributes with an escape sequence, ignored unless sgr
+    pub fn apply(&mut self, escape: &str, palette: &Palette) {
+        if !ansi::is_sgr(escape) {
+            return;
+        }
+        for param in ansi::sgr_params(&escape[2..escape.len() - 1]) {
+            match param {
+                SgrParam::Reset => *self = Attrs::default(),
+                SgrParam::Style(style) => *self.style_mut(style) = true,
+                SgrParam::NoStyle(style) => *self.style_mut(style) = false,
+                SgrParam::Fg(ink) => self.fg = ink.map(|ink| palette.resolve_ink(ink)),
+                SgrParam::Bg(ink) => self.bg = ink.map(|ink| palette.resolve_ink(ink))
+            }
+        }
+    }
+
+    fn style_mut(&mut self, style: Style) -> &mut bool {
+        match style {
+            Style::Bold => &mut self.bold,
+            Style::Faint => &mut self.faint,
+            Style::Italic => &mut self.italic,
+            Style::Underscore => &mut self.underline,
+            Style::Blink => &mut self.blink,
+            Style::Reverse => &mut self.reverse,
+            Style::Concealed => &mut self.concealed,
+            Style::CrossedOut => &mut self.crossedout
+        }
+    }
+
+    /// Foreground and background to draw, after reverse and concealed
+    pub fn colors(&self, theme: &Theme) -> (Rgb, Rgb) {
+        let fg = self.fg.unwrap_or(theme.foreground);
+        let bg = self.bg.unwrap_or(theme.background);
+        let (fg, bg) = if self.reverse { (bg, fg) } else { (fg, bg) };
+        if self.concealed { (bg, bg) } else { (fg, bg) }
+    }
+}
+
+/// The palette and the default colors of a converted document
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Theme {
+    pub palette: Palette,
+    pub background: Rgb,
+    pub foreground: Rgb
+}
+
+impl Theme {
+    /// Light text on the darkest color of the palette
+    pub fn dark(palette: Palette) -> Theme {
+        Theme { palette, background: palette.colors[0], foreground: palette.colors[7] }
+    }
+
+    /// Dark text on the lightest color of the palette
+    pub fn light(palette: Palette) -> Theme {
+        Theme { palette, background: palette.colors[15], foreground: palette.colors[0] }
+    }
+
+    /// Get a theme by name, `dark` or `light`
+    pub fn named(name: &str, palette: Palette) -> Option<Theme> {
+        match name {
+            "dark" => Some(Theme::dark(palette)),
+            "light" => Some(Theme::light(palette)),
+            _ => None
+        }
+    }
+}
+
+/// A document written line by line
+pub trait Convert {
+    /// Write a line without its end of line
+    fn write_line(&mut self, line: &str) -> io::Result<()>;
+    /// Write the end of the document
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Convert every line of an input, then finish the document
+pub fn convert<R: BufRead, C: Convert + ?Sized>(mut input: R, output: &mut C) -> io::Result<()> {
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if input.read_until(b'\n', &mut buf)? == 0 {
+            return output.finish();
+        }
+        let line = String::from_utf8_lossy(&buf);
+        output.write_line(line.trim_end_matches(&['\n', '\r'][..]))?;
+    }
+}
+
+fn hex(rgb: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb.r, rgb.g, rgb.b)
+}
+
+fn escape_xml(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // other control characters are not allowed in xml
+            c if c.is_control() && c != '\t' => {}
+            c => out.push(c)
+        }
+    }
+}
+
+fn decorations(attrs: &Attrs) -> Option<&'static str> {
+    match (attrs.underline, attrs.crossedout) {
+        (true, true) => Some("underline line-through"),
+        (true, false) => Some("underline"),
+        (false, true) => Some("line-through"),
+        (false, false) => None
+    }
+}
+
+/// Write a html page, each line is written as soon as it is read
+pub struct HtmlWriter<W: Write> {
+    out: W,
+    theme: Theme,
+    attrs: Attrs
+}
+
+impl<W: Write> HtmlWriter<W> {
+    /// Start a page without title
+    pub fn new(out: W, theme: Theme) -> io::Result<HtmlWriter<W>> {
+        HtmlWriter::with_title(out, theme, "")
+    }
+
+    /// Start a page
+    pub fn with_title(mut out: W, theme: Theme, title: &str) -> io::Result<HtmlWriter<W>> {
+        let mut escaped = String::new();
+        escape_xml(&mut escaped, title);
+        let (bg, fg) = (hex(theme.background), hex(theme.foreground));
+        write!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n\
+                     <body style=\"margin:0;background:{}\">\n\
+                     <pre style=\"margin:0;padding:8px;color:{};background:{};font-family:monospace\">\n",
+               escaped, bg, fg, bg)?;
+        Ok(HtmlWriter { out, theme, attrs: Attrs::default() })
+    }
+
+    /// Get back the writer
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn style(&self) -> String {
+        let (fg, bg) = self.attrs.colors(&self.theme);
+        let mut style = String::new();
+        if fg != self.theme.foreground {
+            let _ = write!(style, "color:{};", hex(fg));
+        }
+        if bg != self.theme.background {
+            let _ = write!(style, "background:{};", hex(bg));
+        }
+        if self.attrs.bold {
+            style.push_str("font-weight:bold;");
+        }
+        if self.attrs.faint {
+            style.push_str("opacity:0.5;");
+        }
+        if self.attrs.italic {
+            style.push_str("font-style:italic;");
+        }
+        if let Some(decoration) = decorations(&self.attrs) {
+            let _ = write!(style, "text-decoration:{};", decoration);
+        }
+        style
+    }
+}
+
+impl<W: Write> Convert for HtmlWriter<W> {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let mut html = String::new();
+        let mut open = String::new();
+        for token in ansi::tokens(line) {
+            match token {
+                Token::Escape(escape) => self.attrs.apply(escape, &self.theme.palette),
+                Token::Text(text) => {
+                    let style = self.style();
+                    if style != open {
+                        if !open.is_empty() {
+                            html.push_str("</span>");
+                        }
+                        if !style.is_empty() {
+                            let _ = write!(html, "<span style=\"{}\">", style);
+                        }
+                        open = style;
+                    }
+                    escape_xml(&mut html, text);
+                }
+            }
+        }
+        if !open.is_empty() {
+            html.push_str("</span>");
+        }
+        html.push('\n');
+        self.out.write_all(html.as_bytes())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.write_all(b"</pre>\n</body>\n</html>\n")?;
+        self.out.flush()
+    }
+}
+
+const FONT_SIZE: f64 = 14.0;
+const CELL_WIDTH: f64 = 8.4;
+const LINE_HEIGHT: f64 = 18.0;
+const MARGIN: f64 = 8.0;
+
+/// Write a svg image
+///
+/// The size of the image comes first in the document, so the drawing is
+/// kept in memory until `finish`, unlike the parsing of the input.
+pub struct SvgWriter<W: Write> {
+    out: W,
+    theme: Theme,
+    attrs: Attrs,
+    body: String,
+    lines: usize,
+    columns: usize
+}
+
+impl<W: Write> SvgWriter<W> {
+    /// Start an image
+    pub fn new(out: W, theme: Theme) -> SvgWriter<W> {
+        SvgWriter { out, theme, attrs: Attrs::default(), body: String::new(), lines: 0, columns: 0 }
+    }
+
+    /// Get back the writer
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    // The attributes of a tspan, after its x position
+    fn text_attrs(&self, fg: Rgb) -> String {
+        let mut attrs = String::new();
+        if fg != self.theme.foreground {
+            let _ = write!(attrs, " fill=\"{}\"", hex(fg));
+        }
+        if self.attrs.bold {
+            attrs.push_str(" font-weight=\"bold\"");
+        }
+        if self.attrs.faint {
+            attrs.push_str(" opacity=\"0.5\"");
+        }
+        if self.attrs.italic {
+            attrs.push_str(" font-style=\"italic\"");
+        }
+        if let Some(decoration) = decorations(&self.attrs) {
+            let _ = write!(attrs, " text-decoration=\"{}\"", decoration);
+        }
+        attrs
+    }
+}
+
+impl<W: Write> Convert for SvgWriter<W> {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let top = MARGIN + self.lines as f64 * LINE_HEIGHT;
+        let mut rects = String::new();
+        let mut text = String::new();
+        let mut column = 0;
+        for token in ansi::tokens(line) {
+            let chunk = match token {
+                Token::Escape(escape) => {
+                    self.attrs.apply(escape, &self.theme.palette);
+                    continue;
+                }
+                Token::Text(chunk) => chunk
+            };
+            let width: usize = chunk.chars().map(|c| c.width().unwrap_or(0)).sum();
+            let x = MARGIN + column as f64 * CELL_WIDTH;
+            let (fg, bg) = self.attrs.colors(&self.theme);
+            if bg != self.theme.background {
+                let _ = write!(rects, "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                               x, top, width as f64 * CELL_WIDTH, LINE_HEIGHT, hex(bg));
+            }
+            let _ = write!(text, "<tspan x=\"{:.1}\"{}>", x, self.text_attrs(fg));
+            escape_xml(&mut text, chunk);
+            text.push_str("</tspan>");
+            column += width;
+        }
+        self.body.push_str(&rects);
+        if !text.is_empty() {
+            let baseline = top + LINE_HEIGHT * 0.75;
+            let _ = writeln!(self.body, "<text y=\"{:.1}\" xml:space=\"preserve\">{}</text>", baseline, text);
+        }
+        self.lines += 1;
+        self.columns = self.columns.max(column);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let width = 2.0 * MARGIN + self.columns as f64 * CELL_WIDTH;
+        let height = 2.0 * MARGIN + self.lines as f64 * LINE_HEIGHT;
+        write!(self.out, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.1}\" height=\"{:.1}\" \
+                          viewBox=\"0 0 {:.1} {:.1}\" font-family=\"monospace\" font-size=\"{}\" fill=\"{}\">\n\
+                          <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+               width, height, width, height, FONT_SIZE, hex(self.theme.foreground), hex(self.theme.background))?;
+        self.out.write_all(self.body.as_bytes())?;
+        self.body.clear();
+        self.out.write_all(b"</svg>\n")?;
+        self.out.flush()
+    }
+}
+
+/// Write the text without its escape sequences
+pub struct TextWriter<W: Write> {
+    out: W
+}
+
+impl<W: Write> TextWriter<W> {
+    pub fn new(out: W) -> TextWriter<W> {
+        TextWriter { out }
+    }
+
+    /// Get back the writer
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> Convert for TextWriter<W> {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.out, "{}", ansi::strip(line))
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{convert, Convert, HtmlWriter, SvgWriter, Theme, TextWriter};
+    use rgb::Palette;
+
+    const HEADER_END: &str = "font-family:monospace\">\n";
+
+    // The lines of the body of a page
+    fn html(input: &str) -> String {
+        let mut writer = HtmlWriter::new(Vec::new(), Theme::dark(Palette::XTERM)).unwrap();
+        convert(input.as_bytes(), &mut writer).unwrap();
+        let page = String::from_utf8(writer.into_inner()).unwrap();
+        let start = page.find(HEADER_END).unwrap() + HEADER_END.len();
+        let end = page.rfind("</pre>").unwrap();
+        page[start..end].to_string()
+    }
+
+    #[test]
+    fn html_escaping() {
+        assert_eq!(html("<a href=\"x\">&amp;</a>\x07"), "&lt;a href=&quot;x&quot;&gt;&amp;amp;&lt;/a&gt;\n");
+        let mut writer = HtmlWriter::with_title(Vec::new(), Theme::dark(Palette::XTERM), "a<b").unwrap();
+        writer.finish().unwrap();
+        assert!(String::from_utf8(writer.into_inner()).unwrap().contains("<title>a&lt;b</title>"));
+    }
+
+    #[test]
+    fn html_spans() {
+        assert_eq!(html("\x1b[1;31merror\x1b[4m:\x1b[0m ok"),
+                   "<span style=\"color:#cd0000;font-weight:bold;\">error</span>\
+                    <span style=\"color:#cd0000;font-weight:bold;text-decoration:underline;\">:</span> ok\n");
+        // the attributes go on to the next line, the span is closed at the end of each line
+        assert_eq!(html("\x1b[32ma\nb\x1b[39m c"),
+                   "<span style=\"color:#00cd00;\">a</span>\n<span style=\"color:#00cd00;\">b</span> c\n");
+        assert_eq!(html("\x1b[31m\x1b[0mplain"), "plain\n");
+    }
+
+    #[test]
+    fn html_colors() {
+        assert_eq!(html("\x1b[38;5;196ma\x1b[48;2;1;2;3mb\x1b[38;5;244;7mc"),
+                   "<span style=\"color:#ff0000;\">a</span>\
+                    <span style=\"color:#ff0000;background:#010203;\">b</span>\
+                    <span style=\"color:#010203;background:#808080;\">c</span>\n");
+    }
+
+    #[test]
+    fn svg() {
+        let mut writer = SvgWriter::new(Vec::new(), Theme::dark(Palette::XTERM));
+        convert(&b"\x1b[1;44ma<\n\nb"[..], &mut writer).unwrap();
+        assert_eq!(String::from_utf8(writer.into_inner()).unwrap(), "\
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"32.8\" height=\"70.0\" viewBox=\"0 0 32.8 70.0\" \
+font-family=\"monospace\" font-size=\"14\" fill=\"#e5e5e5\">
+<rect width=\"100%\" height=\"100%\" fill=\"#000000\"/>
+<rect x=\"8.0\" y=\"8.0\" width=\"16.8\" height=\"18.0\" fill=\"#0000ee\"/>\
+<text y=\"21.5\" xml:space=\"preserve\"><tspan x=\"8.0\" font-weight=\"bold\">a&lt;</tspan></text>
+<rect x=\"8.0\" y=\"44.0\" width=\"8.4\" height=\"18.0\" fill=\"#0000ee\"/>\
+<text y=\"57.5\" xml:space=\"preserve\"><tspan x=\"8.0\" font-weight=\"bold\">b</tspan></text>
+</svg>
+");
+    }
+
+    #[test]
+    fn text() {
+        let mut writer = TextWriter::new(Vec::new());
+        convert(&b"\x1b[1ma\x1b[0m\r\nb"[..], &mut writer).unwrap();
+        assert_eq!(writer.into_inner(), b"a\nb\n");
+    }
+}
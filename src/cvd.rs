@@ -0,0 +1,221 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Color vision deficiencies: simulation, a safe palette and status markers.
+//
+// ```Rust
+// colorize::cvd::set_cvd_safe(true);
+// println!("{}", Status::Failure.paint("3 tests failed"));
+// ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use Color;
+use Color::{Red, Green, Yellow, Blue};
+use Style::Bold;
+use internal;
+use rgb::{self, ColorDepth, Rgb};
+
+/// A color vision deficiency
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Deficiency {
+    /// No red cones
+    Protanopia,
+    /// No green cones
+    Deuteranopia,
+    /// No blue cones
+    Tritanopia
+}
+
+// Machado, Oliveira and Fernandes 2009, severity 1, on linear rgb
+const PROTANOPIA: [[f64; 3]; 3] = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998]
+];
+const DEUTERANOPIA: [[f64; 3]; 3] = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881]
+];
+const TRITANOPIA: [[f64; 3]; 3] = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900]
+];
+
+/// How a color is seen with a deficiency
+pub fn simulate(rgb: Rgb, deficiency: Deficiency) -> Rgb {
+    let m = match deficiency {
+        Deficiency::Protanopia => &PROTANOPIA,
+        Deficiency::Deuteranopia => &DEUTERANOPIA,
+        Deficiency::Tritanopia => &TRITANOPIA
+    };
+    let c = [rgb::to_linear(rgb.r), rgb::to_linear(rgb.g), rgb::to_linear(rgb.b)];
+    let row = |i: usize| rgb::from_linear(m[i][0] * c[0] + m[i][1] * c[1] + m[i][2] * c[2]);
+    Rgb::new(row(0), row(1), row(2))
+}
+
+/// The Okabe-Ito palette, distinguishable with every deficiency
+pub mod okabe_ito {
+    use rgb::Rgb;
+
+    pub const BLACK: Rgb = Rgb::new(0x00, 0x00, 0x00);
+    pub const ORANGE: Rgb = Rgb::new(0xe6, 0x9f, 0x00);
+    pub const SKY_BLUE: Rgb = Rgb::new(0x56, 0xb4, 0xe9);
+    pub const BLUISH_GREEN: Rgb = Rgb::new(0x00, 0x9e, 0x73);
+    pub const YELLOW: Rgb = Rgb::new(0xf0, 0xe4, 0x42);
+    pub const BLUE: Rgb = Rgb::new(0x00, 0x72, 0xb2);
+    pub const VERMILLION: Rgb = Rgb::new(0xd5, 0x5e, 0x00);
+    pub const REDDISH_PURPLE: Rgb = Rgb::new(0xcc, 0x79, 0xa7);
+}
+
+static CVD_SAFE: AtomicBool = AtomicBool::new(false);
+
+/// Remap the colors to the Okabe-Ito palette, for every colored text
+///
+/// Red, green, yellow, blue, magenta and cyan, normal or bright, in the
+/// foreground or the background, are replaced. With 16 colors they become
+/// bright red, cyan, bright yellow, blue, bright magenta and bright cyan.
+pub fn set_cvd_safe(enabled: bool) {
+    CVD_SAFE.store(enabled, Ordering::Relaxed)
+}
+
+/// Is the cvd safe mode enabled
+pub fn cvd_safe() -> bool {
+    CVD_SAFE.load(Ordering::Relaxed)
+}
+
+// The sgr parameters written for an attribute code
+pub fn code(code: i32) -> String {
+    if cvd_safe() { safe_code(code, rgb::color_depth()) } else { code.to_string() }
+}
+
+// The sgr parameters of the safe replacement of a color code
+//
+// With 16 colors the nearest colors of the palette mostly fall back on
+// the original ones, a fixed replacement is used instead.
+fn safe_code(code: i32, depth: ColorDepth) -> String {
+    let (base, background) = match code {
+        30..=37 => (code - 30, false),
+        90..=97 => (code - 90, false),
+        40..=47 => (code - 40, true),
+        100..=107 => (code - 100, true),
+        _ => return code.to_string()
+    };
+    // the Okabe-Ito color, and the 16 colors one as an offset from 30
+    let (safe, ansi) = match base {
+        1 => (okabe_ito::VERMILLION, 61),
+        2 => (okabe_ito::BLUISH_GREEN, 6),
+        3 => (okabe_ito::YELLOW, 63),
+        4 => (okabe_ito::BLUE, 4),
+        5 => (okabe_ito::REDDISH_PURPLE, 65),
+        6 => (okabe_ito::SKY_BLUE, 66),
+        _ => return code.to_string()
+    };
+    match depth {
+        ColorDepth::Ansi16 => (30 + ansi + if background { 10 } else { 0 }).to_string(),
+        _ if background => rgb::bg_code(safe, depth),
+        _ => rgb::fg_code(safe, depth)
+    }
+}
+
+/// The outcome of an operation, shown with a symbol as well as a color
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Success,
+    Failure,
+    Warning,
+    Info
+}
+
+impl Status {
+    /// The symbol written before the text
+    pub fn symbol(&self) -> &'static str {
+        match *self {
+            Status::Success => "✔",
+            Status::Failure => "✖",
+            Status::Warning => "⚠",
+            Status::Info => "ℹ"
+        }
+    }
+
+    /// The color of the symbol and the text
+    pub fn color(&self) -> Color {
+        match *self {
+            Status::Success => Green,
+            Status::Failure => Red,
+            Status::Warning => Yellow,
+            Status::Info => Blue
+        }
+    }
+
+    /// Write a text after the symbol, failures are also in bold
+    pub fn paint(&self, text: &str) -> String {
+        let line = internal::pack(self.color(), format!("{} {}", self.symbol(), text));
+        match *self {
+            Status::Failure => internal::pack(Bold, line),
+            _ => line
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{okabe_ito, safe_code, simulate, Deficiency};
+    use rgb::{ColorDepth, Rgb};
+
+    #[test]
+    fn safe_ansi16() {
+        let depth = ColorDepth::Ansi16;
+        assert_eq!(safe_code(31, depth), "91");
+        assert_eq!(safe_code(32, depth), "36");
+        assert_eq!(safe_code(92, depth), "36");
+        assert_eq!(safe_code(41, depth), "101");
+        assert_eq!(safe_code(42, depth), "46");
+        assert_eq!(safe_code(30, depth), "30");
+        assert_eq!(safe_code(1, depth), "1");
+    }
+
+    #[test]
+    fn safe_rgb() {
+        let safe = okabe_ito::VERMILLION;
+        assert_eq!(safe_code(31, ColorDepth::TrueColor), format!("38;2;{};{};{}", safe.r, safe.g, safe.b));
+        assert_eq!(safe_code(42, ColorDepth::TrueColor), "48;2;0;158;115");
+        assert_eq!(safe_code(37, ColorDepth::TrueColor), "37");
+    }
+
+    #[test]
+    fn simulation() {
+        let (red, green, blue) = (Rgb::new(255, 0, 0), Rgb::new(0, 255, 0), Rgb::new(0, 0, 255));
+        assert_eq!(simulate(red, Deficiency::Protanopia), Rgb::new(109, 95, 0));
+        assert_eq!(simulate(green, Deficiency::Protanopia), Rgb::new(255, 229, 0));
+        assert_eq!(simulate(red, Deficiency::Deuteranopia), Rgb::new(163, 144, 0));
+        assert_eq!(simulate(green, Deficiency::Deuteranopia), Rgb::new(239, 214, 58));
+        assert_eq!(simulate(blue, Deficiency::Tritanopia), Rgb::new(0, 107, 150));
+        // greys are seen the same
+        for &d in &[Deficiency::Protanopia, Deficiency::Deuteranopia, Deficiency::Tritanopia] {
+            for &v in &[0, 128, 255] {
+                assert_eq!(simulate(Rgb::new(v, v, v), d), Rgb::new(v, v, v));
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Colored `Debug` output of any value.
+//
+// ```Rust
+// println!("{:#?}", colorize::debug(&value));
+// ```
+
+use std::fmt;
+
+use Color::{Cyan, Blue, Green, Yellow, Magenta};
+use Style::Bold;
+use paint::Paint;
+use term::{self, Stream};
+
+/// Paints of the parts of a `Debug` output
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DebugPalette {
+    pub type_name: Paint,
+    pub field: Paint,
+    pub string: Paint,
+    pub number: Paint,
+    pub boolean: Paint,
+    pub punctuation: Paint
+}
+
+impl DebugPalette {
+    /// The default palette
+    pub const DEFAULT: DebugPalette = DebugPalette {
+        type_name: Paint::new().fg(Cyan).style(Bold),
+        field: Paint::new().fg(Blue),
+        string: Paint::new().fg(Green),
+        number: Paint::new().fg(Yellow),
+        boolean: Paint::new().fg(Magenta),
+        punctuation: Paint::new()
+    };
+}
+
+impl Default for DebugPalette {
+    fn default() -> DebugPalette {
+        DebugPalette::DEFAULT
+    }
+}
+
+/// Wrap a value to color its `Debug` output
+///
+/// The output is not colored when colors are disabled for the standard output.
+pub fn debug<T: fmt::Debug + ?Sized>(value: &T) -> ColoredDebug<'_, T> {
+    ColoredDebug { value, palette: DebugPalette::DEFAULT }
+}
+
+/// A value whose `Debug` output is colored, see `debug`
+pub struct ColoredDebug<'a, T: ?Sized + 'a> {
+    value: &'a T,
+    palette: DebugPalette
+}
+
+impl<'a, T: fmt::Debug + ?Sized> ColoredDebug<'a, T> {
+    /// Set the palette
+    pub fn palette(mut self, palette: DebugPalette) -> ColoredDebug<'a, T> {
+        self.palette = palette;
+        self
+    }
+}
+
+impl<'a, T: fmt::Debug + ?Sized> fmt::Debug for ColoredDebug<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = if f.alternate() { format!("{:#?}", self.value) } else { format!("{:?}", self.value) };
+        if term::colors_enabled(Stream::Stdout) {
+            f.write_str(&highlight(&text, &self.palette))
+        } else {
+            f.write_str(&text)
+        }
+    }
+}
+
+// Length of the quoted literal starting the text, up to the closing quote
+fn quoted(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i + 1);
+        } else if c == '\n' {
+            return None;
+        }
+    }
+    None
+}
+
+// Length of the number starting the text, a dot is part of it before a digit
+fn number(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut len = 0;
+    while let Some(&c) = bytes.get(len) {
+        let ok = c.is_ascii_alphanumeric() || c == b'_'
+            || (c == b'.' && bytes.get(len + 1).is_some_and(u8::is_ascii_digit))
+            || ((c == b'-' || c == b'+') && (len == 0 || bytes[len - 1] == b'e' || bytes[len - 1] == b'E'));
+        if !ok {
+            break;
+        }
+        len += 1;
+    }
+    len
+}
+
+// Split the text in tokens and paint each of them
+fn highlight(text: &str, palette: &DebugPalette) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let (len, paint) = if c == '"' || c == '\'' {
+            match quoted(rest, c) {
+                Some(len) => (len, palette.string),
+                None => (c.len_utf8(), palette.punctuation)
+            }
+        } else if c.is_ascii_digit()
+                  || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+            (number(rest), palette.number)
+        } else if c.is_alphabetic() || c == '_' {
+            let mut len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            // a path such as `std::io::Error`
+            while rest[len..].starts_with("::")
+                  && rest[len + 2..].starts_with(|c: char| c.is_alphabetic() || c == '_') {
+                len += 2 + rest[len + 2..].find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len() - len - 2);
+            }
+            let word = &rest[..len];
+            let after = rest[len..].trim_start_matches(' ');
+            let paint = if word == "true" || word == "false" {
+                palette.boolean
+            } else if word == "inf" || word == "NaN" {
+                palette.number
+            } else if after.starts_with(':') && !after.starts_with("::") {
+                palette.field
+            } else if word.starts_with(char::is_uppercase) || word.contains("::") {
+                palette.type_name
+            } else {
+                Paint::new()
+            };
+            (len, paint)
+        } else if c.is_whitespace() {
+            (c.len_utf8(), Paint::new())
+        } else {
+            (c.len_utf8(), palette.punctuation)
+        };
+        let (token, next) = rest.split_at(len);
+        out.push_str(&paint.paint(String::from(token)));
+        rest = next;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{highlight, DebugPalette};
+
+    const PALETTE: DebugPalette = DebugPalette::DEFAULT;
+
+    fn num(s: &str) -> String { PALETTE.number.paint(String::from(s)) }
+    fn punct(s: &str) -> String { PALETTE.punctuation.paint(String::from(s)) }
+    fn type_name(s: &str) -> String { PALETTE.type_name.paint(String::from(s)) }
+    fn field(s: &str) -> String { PALETTE.field.paint(String::from(s)) }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(highlight("0..10", &PALETTE), num("0") + &punct(".") + &punct(".") + &num("10"));
+        assert_eq!(highlight("1..=2", &PALETTE),
+                   num("1") + &punct(".") + &punct(".") + &punct("=") + &num("2"));
+    }
+
+    #[test]
+    fn negatives() {
+        assert_eq!(highlight("-5", &PALETTE), num("-5"));
+        assert_eq!(highlight("[-1, 2]", &PALETTE),
+                   punct("[") + &num("-1") + &punct(",") + " " + &num("2") + &punct("]"));
+        assert_eq!(highlight("a - 1", &PALETTE), "a ".to_string() + &punct("-") + " " + &num("1"));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(highlight("1.5", &PALETTE), num("1.5"));
+        assert_eq!(highlight("-2.5e-3", &PALETTE), num("-2.5e-3"));
+        assert_eq!(highlight("1.", &PALETTE), num("1") + &punct("."));
+        assert_eq!(highlight("inf", &PALETTE), num("inf"));
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(highlight("std::io::Error", &PALETTE), type_name("std::io::Error"));
+        assert_eq!(highlight("Point { x: 1 }", &PALETTE),
+                   type_name("Point") + " " + &punct("{") + " " + &field("x") + &punct(":")
+                   + " " + &num("1") + " " + &punct("}"));
+    }
+}
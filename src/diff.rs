@@ -0,0 +1,427 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Colored diffs of two texts, unified or side by side.
+//
+// ```Rust
+// print!("{}", colorize::diff::Diff::new().labels("old", "new").render(old, new));
+// ```
+
+use Color::{Red, Green, Cyan, BrightBlack};
+use BgColor::{Redb, Greenb};
+use Style::Bold;
+use internal;
+use table::{self, Align};
+use wrap;
+
+/// An edit turning a sequence into another one
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Op {
+    /// Items at these positions in the old and new sequences are equal
+    Equal(usize, usize),
+    /// The item at this position in the old sequence is removed
+    Delete(usize),
+    /// The item at this position in the new sequence is inserted
+    Insert(usize)
+}
+
+/// Shortest edit script between two sequences, using the Myers algorithm
+///
+/// The linear space variant is used, the sequences are split around the
+/// middle of the script until only insertions or deletions remain.
+pub fn ops<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Op> {
+    let size = 2 * ((a.len() + b.len()).div_ceil(2) + 1) + 1;
+    let (mut forward, mut backward) = (vec![0; size], vec![0; size]);
+    let mut ops = Vec::new();
+    conquer(a, b, (0, 0), &mut forward, &mut backward, &mut ops);
+    // deletions first in each change, so removed and added lines pair up
+    let mut start = 0;
+    while start < ops.len() {
+        let end = ops[start..].iter().position(|op| matches!(*op, Op::Equal(..)))
+            .map_or(ops.len(), |p| start + p);
+        ops[start..end].sort_by_key(|op| !matches!(*op, Op::Delete(_)));
+        start = end + 1;
+    }
+    ops
+}
+
+// Edit script of `a` and `b`, which start at `origin` in the whole sequences
+fn conquer<T: PartialEq>(a: &[T], b: &[T], origin: (usize, usize), forward: &mut [isize],
+                         backward: &mut [isize], ops: &mut Vec<Op>) {
+    let prefix = a.iter().zip(b).take_while(|&(x, y)| x == y).count();
+    for i in 0..prefix {
+        ops.push(Op::Equal(origin.0 + i, origin.1 + i));
+    }
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let (x0, y0) = (origin.0 + prefix, origin.1 + prefix);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|&(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    if a.is_empty() {
+        ops.extend((0..b.len()).map(|y| Op::Insert(y0 + y)));
+    } else if b.is_empty() {
+        ops.extend((0..a.len()).map(|x| Op::Delete(x0 + x)));
+    } else {
+        let (x, y) = middle_snake(a, b, forward, backward);
+        conquer(&a[..x], &b[..y], (x0, y0), forward, backward, ops);
+        conquer(&a[x..], &b[y..], (x0 + x, y0 + y), forward, backward, ops);
+    }
+    for i in 0..suffix {
+        ops.push(Op::Equal(x0 + a.len() + i, y0 + b.len() + i));
+    }
+}
+
+// A point of an optimal edit script, searched from both ends at once
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T], forward: &mut [isize],
+                              backward: &mut [isize]) -> (usize, usize) {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta & 1 == 1;
+    let off = (forward.len() / 2) as isize;
+    let at = |k: isize| (k + off) as usize;
+    forward[at(1)] = 0;
+    backward[at(1)] = 0;
+    for d in 0..=(n + m + 1) / 2 {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let (start_x, start_y) = (x, x - k);
+            let mut y = start_y;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            if odd && (k - delta).abs() < d && x <= n && y <= m && x + backward[at(delta - k)] >= n {
+                return (start_x as usize, start_y as usize);
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            if !odd && (k - delta).abs() <= d && x <= n && y <= m && x + forward[at(delta - k)] >= n {
+                return ((n - x) as usize, (m - y) as usize);
+            }
+        }
+    }
+    // not reached, the two searches always meet
+    (a.len(), 0)
+}
+
+// Split a line in words, runs of spaces and single punctuation characters
+fn words(line: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut last: Option<u8> = None;
+    for (i, c) in line.char_indices() {
+        let class = if c.is_alphanumeric() || c == '_' { 0 } else if c.is_whitespace() { 1 } else { 2 };
+        if i > start && (last != Some(class) || class == 2) {
+            words.push(&line[start..i]);
+            start = i;
+        }
+        last = Some(class);
+    }
+    if start < line.len() {
+        words.push(&line[start..]);
+    }
+    words
+}
+
+// Color a changed line, the words that differ from `other` are highlighted
+fn highlight(line: &str, other: &str, removed: bool) -> String {
+    let (a, b) = (words(line), words(other));
+    let ops = if removed { ops(&a, &b) } else { ops(&b, &a) };
+    // runs of words all changed or all unchanged
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for op in ops {
+        let (word, changed) = match (op, removed) {
+            (Op::Equal(i, _), true) | (Op::Equal(_, i), false) => (a[i], false),
+            (Op::Delete(i), true) | (Op::Insert(i), false) => (a[i], true),
+            _ => continue
+        };
+        match runs.last_mut() {
+            Some(run) if run.0 == changed => run.1.push_str(word),
+            _ => runs.push((changed, String::from(word)))
+        }
+    }
+    runs.into_iter().map(|(changed, run)| match (changed, removed) {
+        (false, true) => internal::pack(Red, run),
+        (false, false) => internal::pack(Green, run),
+        (true, true) => internal::pack(Bold, internal::pack(Redb, run)),
+        (true, false) => internal::pack(Bold, internal::pack(Greenb, run))
+    }).collect()
+}
+
+// Part of a hunk, removed lines are paired with the added lines replacing them
+enum Block<'a> {
+    Context(usize, usize, &'a str),
+    Change(Vec<(usize, &'a str)>, Vec<(usize, &'a str)>)
+}
+
+/// A diff renderer
+#[derive(Clone, Debug)]
+pub struct Diff {
+    context: usize,
+    side_by_side: Option<usize>,
+    labels: Option<(String, String)>
+}
+
+impl Default for Diff {
+    fn default() -> Diff {
+        Diff::new()
+    }
+}
+
+impl Diff {
+    /// Create a unified diff renderer with 3 lines of context
+    pub fn new() -> Diff {
+        Diff { context: 3, side_by_side: None, labels: None }
+    }
+
+    /// Set the number of unchanged lines shown around the changes
+    pub fn context(mut self, lines: usize) -> Diff {
+        self.context = lines;
+        self
+    }
+
+    /// Show the texts side by side, in `width` columns
+    pub fn side_by_side(mut self, width: usize) -> Diff {
+        self.side_by_side = Some(width);
+        self
+    }
+
+    /// Set the names of the old and new texts, shown in the header
+    pub fn labels(mut self, old: &str, new: &str) -> Diff {
+        self.labels = Some((String::from(old), String::from(new)));
+        self
+    }
+
+    /// Render the differences between two texts
+    pub fn render(&self, old: &str, new: &str) -> String {
+        let (a, b): (Vec<&str>, Vec<&str>) = (old.lines().collect(), new.lines().collect());
+        let ops = ops(&a, &b);
+        let mut out = String::new();
+        if let Some((ref old, ref new)) = self.labels {
+            match self.side_by_side {
+                Some(width) => {
+                    let half = width.saturating_sub(3) / 2;
+                    out.push_str(&internal::pack(Bold, format!(
+                        "{} │ {}", table::pad(&wrap::truncate(old, half, "…"), half, Align::Left),
+                        wrap::truncate(new, half, "…"))));
+                }
+                None => {
+                    out.push_str(&internal::pack(Bold, format!("--- {}", old)));
+                    out.push('\n');
+                    out.push_str(&internal::pack(Bold, format!("+++ {}", new)));
+                }
+            }
+            out.push('\n');
+        }
+        // position in both texts before each operation
+        let mut pos = Vec::with_capacity(ops.len() + 1);
+        let (mut x, mut y) = (0, 0);
+        for op in ops.iter() {
+            pos.push((x, y));
+            match *op {
+                Op::Equal(..) => { x += 1; y += 1; }
+                Op::Delete(_) => x += 1,
+                Op::Insert(_) => y += 1
+            }
+        }
+        pos.push((x, y));
+
+        for (start, end) in self.hunks(&ops) {
+            let blocks = blocks(&ops[start..end], &a, &b);
+            let (old_len, new_len) = (pos[end].0 - pos[start].0, pos[end].1 - pos[start].1);
+            // an empty range starts at the line before it
+            let first = |p: usize, len: usize| if len == 0 { p } else { p + 1 };
+            out.push_str(&internal::pack(Cyan, format!(
+                "@@ -{},{} +{},{} @@", first(pos[start].0, old_len), old_len,
+                first(pos[start].1, new_len), new_len)));
+            out.push('\n');
+            match self.side_by_side {
+                Some(width) => out.push_str(&side_by_side(&blocks, width)),
+                None => out.push_str(&unified(&blocks))
+            }
+        }
+        out
+    }
+
+    // Ranges of the operations shown, changes with their context
+    fn hunks(&self, ops: &[Op]) -> Vec<(usize, usize)> {
+        let mut hunks: Vec<(usize, usize)> = Vec::new();
+        for (i, op) in ops.iter().enumerate() {
+            if let Op::Equal(..) = *op {
+                continue;
+            }
+            let start = i.saturating_sub(self.context);
+            let end = (i + self.context + 1).min(ops.len());
+            match hunks.last_mut() {
+                Some(last) if start <= last.1 => last.1 = end,
+                _ => hunks.push((start, end))
+            }
+        }
+        hunks
+    }
+}
+
+fn blocks<'a>(ops: &[Op], a: &[&'a str], b: &[&'a str]) -> Vec<Block<'a>> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        if let Op::Equal(x, y) = ops[i] {
+            blocks.push(Block::Context(x, y, a[x]));
+            i += 1;
+            continue;
+        }
+        let mut removed = Vec::new();
+        while let Some(&Op::Delete(x)) = ops.get(i) {
+            removed.push((x, a[x]));
+            i += 1;
+        }
+        let mut added = Vec::new();
+        while let Some(&Op::Insert(y)) = ops.get(i) {
+            added.push((y, b[y]));
+            i += 1;
+        }
+        blocks.push(Block::Change(removed, added));
+    }
+    blocks
+}
+
+fn changed(line: &str, other: Option<&str>, removed: bool) -> String {
+    match other {
+        Some(other) => highlight(line, other, removed),
+        None => internal::pack(if removed { Red } else { Green }, String::from(line))
+    }
+}
+
+fn unified(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    for block in blocks {
+        match *block {
+            Block::Context(_, _, text) => {
+                out.push_str(&format!(" {}\n", text));
+            }
+            Block::Change(ref removed, ref added) => {
+                for (n, &(_, text)) in removed.iter().enumerate() {
+                    out.push_str(&internal::pack(Red, String::from("-")));
+                    out.push_str(&changed(text, added.get(n).map(|l| l.1), true));
+                    out.push('\n');
+                }
+                for (n, &(_, text)) in added.iter().enumerate() {
+                    out.push_str(&internal::pack(Green, String::from("+")));
+                    out.push_str(&changed(text, removed.get(n).map(|l| l.1), false));
+                    out.push('\n');
+                }
+            }
+        }
+    }
+    out
+}
+
+fn side_by_side(blocks: &[Block], width: usize) -> String {
+    // each half holds a line number, a space and the text
+    let half = width.saturating_sub(3) / 2;
+    let text_width = half.saturating_sub(5);
+    let cell = |line: Option<(usize, String)>| match line {
+        Some((n, text)) => format!("{} {}", internal::pack(BrightBlack, format!("{:>4}", n + 1)),
+                                   table::pad(&wrap::truncate(&text, text_width, "…"),
+                                              text_width, Align::Left)),
+        None => " ".repeat(text_width + 5)
+    };
+
+    let mut out = String::new();
+    for block in blocks {
+        match *block {
+            Block::Context(x, y, text) => {
+                out.push_str(&format!("{} │ {}\n", cell(Some((x, String::from(text)))),
+                                      cell(Some((y, String::from(text))))));
+            }
+            Block::Change(ref removed, ref added) => {
+                for n in 0..removed.len().max(added.len()) {
+                    let (old, new) = (removed.get(n), added.get(n));
+                    let left = old.map(|&(x, text)| (x, changed(text, new.map(|l| l.1), true)));
+                    let right = new.map(|&(y, text)| (y, changed(text, old.map(|l| l.1), false)));
+                    out.push_str(&format!("{} │ {}\n", cell(left), cell(right)));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ops, Op};
+
+    // Number of insertions and deletions, after checking the script is valid
+    fn distance(a: &[&str], b: &[&str]) -> usize {
+        let (mut x, mut y, mut d) = (0, 0, 0);
+        for op in ops(a, b) {
+            match op {
+                Op::Equal(i, j) => {
+                    assert!(i == x && j == y && a[i] == b[j]);
+                    x += 1;
+                    y += 1;
+                }
+                Op::Delete(i) => { assert_eq!(i, x); x += 1; d += 1; }
+                Op::Insert(j) => { assert_eq!(j, y); y += 1; d += 1; }
+            }
+        }
+        assert_eq!((x, y), (a.len(), b.len()));
+        d
+    }
+
+    #[test]
+    fn shortest_script() {
+        assert_eq!(distance(&[], &[]), 0);
+        assert_eq!(distance(&["a", "b"], &[]), 2);
+        assert_eq!(distance(&["a", "b", "c"], &["a", "b", "c"]), 0);
+        assert_eq!(distance(&["a", "b", "c", "a", "b", "b", "a"], &["c", "b", "a", "b", "a", "c"]), 5);
+        assert_eq!(distance(&["x", "a", "b", "y"], &["a", "z", "b"]), 3);
+    }
+
+    #[test]
+    fn deletions_before_insertions() {
+        assert_eq!(ops(&["a", "b", "c"], &["a", "x", "c"]),
+                   vec![Op::Equal(0, 0), Op::Delete(1), Op::Insert(1), Op::Equal(2, 2)]);
+    }
+
+    #[test]
+    fn large_unrelated_texts() {
+        let a: Vec<String> = (0..3000).map(|i| format!("old {}", i)).collect();
+        let b: Vec<String> = (0..3000).map(|i| format!("new {}", i)).collect();
+        assert_eq!(ops(&a, &b).len(), 6000);
+    }
+}
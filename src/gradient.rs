@@ -0,0 +1,140 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Per character color gradients.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use ansi::{self, Token};
+use rgb::{self, ColorDepth, Oklab, Palette, Rgb};
+use term::{self, Stream};
+use BgColor;
+use internal;
+
+/// Color of the position `t` (from 0 to 1) of a gradient going through `stops`
+pub fn at(stops: &[Oklab], t: f64) -> Rgb {
+    if stops.len() == 1 {
+        return Rgb::from(stops[0]);
+    }
+    let pos = t * (stops.len() - 1) as f64;
+    let i = (pos.floor() as usize).min(stops.len() - 2);
+    Rgb::from(stops[i].lerp(stops[i + 1], pos - i as f64))
+}
+
+/// Color each visible character of `text` along a gradient
+///
+/// Nothing is colored when colors are disabled for the standard output.
+/// With 16 colors, the terminal capabilities and the cvd safe mode apply
+/// to the nearest colors.
+pub fn paint(text: String, stops: &[Rgb], background: bool) -> String {
+    if stops.is_empty() || !term::colors_enabled(Stream::Stdout) {
+        return text;
+    }
+    render(text, stops, background, rgb::color_depth())
+}
+
+fn render(text: String, stops: &[Rgb], background: bool, depth: ColorDepth) -> String {
+    let stops: Vec<Oklab> = stops.iter().map(|&c| Oklab::from(c)).collect();
+    let count = ansi::tokens(&text).map(|t| match t {
+        Token::Text(s) => s.graphemes(true).count(),
+        Token::Escape(_) => 0
+    }).sum::<usize>();
+
+    let mut out = String::with_capacity(text.len() * 8);
+    let mut pos = 0;
+    let mut colored = false;
+    for token in ansi::tokens(&text) {
+        match token {
+            Token::Escape(s) => out.push_str(s),
+            Token::Text(s) => for g in s.graphemes(true) {
+                let t = if count > 1 { pos as f64 / (count - 1) as f64 } else { 0.0 };
+                if let Some(code) = code(at(&stops, t), depth, background) {
+                    out.push_str(&format!("\x1b[{}m", code));
+                    colored = true;
+                }
+                out.push_str(g);
+                pos += 1;
+            }
+        }
+    }
+    if colored {
+        let (fg, bg) = internal::get_glob();
+        out.push_str(&format!("\x1b[0;{};{}m", fg, bg));
+    }
+    out
+}
+
+// The sgr parameters of a gradient color, none if the terminal can't show it
+fn code(color: Rgb, depth: ColorDepth, background: bool) -> Option<String> {
+    match depth {
+        ColorDepth::Ansi16 => {
+            let nearest = Palette::XTERM.nearest(color);
+            if background { internal::code(BgColor::from_fg(nearest)) } else { internal::code(nearest) }
+        }
+        _ if background => Some(rgb::bg_code(color, depth)),
+        _ => Some(rgb::fg_code(color, depth))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{at, render};
+    use rgb::{ColorDepth, Oklab, Rgb};
+
+    const RED: Rgb = Rgb { r: 255, g: 0, b: 0 };
+    const BLUE: Rgb = Rgb { r: 0, g: 0, b: 255 };
+
+    #[test]
+    fn at_endpoints() {
+        let stops = [Oklab::from(RED), Oklab::from(BLUE)];
+        assert_eq!(at(&stops, 0.0), RED);
+        assert_eq!(at(&stops, 1.0), BLUE);
+        assert_eq!(at(&[Oklab::from(RED)], 0.5), RED);
+    }
+
+    #[test]
+    fn at_midpoints() {
+        let green = Rgb::new(0, 255, 0);
+        let stops = [Oklab::from(RED), Oklab::from(green), Oklab::from(BLUE)];
+        assert_eq!(at(&stops, 0.5), green);
+        let grey = at(&[Oklab::from(Rgb::new(0, 0, 0)), Oklab::from(Rgb::new(255, 255, 255))], 0.5);
+        assert!(grey.r == grey.g && grey.g == grey.b && grey.r > 64 && grey.r < 192);
+    }
+
+    #[test]
+    fn render_empty() {
+        assert_eq!(render(String::new(), &[RED, BLUE], false, ColorDepth::TrueColor), "");
+    }
+
+    #[test]
+    fn render_single_char() {
+        assert_eq!(render("a".to_string(), &[RED, BLUE], false, ColorDepth::TrueColor),
+                   "\x1b[38;2;255;0;0ma\x1b[0;39;49m");
+        assert_eq!(render("a".to_string(), &[RED, BLUE], true, ColorDepth::Ansi256),
+                   "\x1b[48;5;196ma\x1b[0;39;49m");
+    }
+
+    #[test]
+    fn render_graphemes() {
+        let out = render("e\u{301}\x1b[1mb".to_string(), &[RED, BLUE], false, ColorDepth::TrueColor);
+        assert_eq!(out, "\x1b[38;2;255;0;0me\u{301}\x1b[1m\x1b[38;2;0;0;255mb\x1b[0;39;49m");
+    }
+}
@@ -0,0 +1,474 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Pretty printing and syntax highlighting of json documents.
+//
+// A text can hold several documents one after the other, like json lines.
+// Invalid json is written unchanged, with the location of the error
+// highlighted. Documents nested more than 128 levels deep are invalid.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use Color::{Blue, Green, Yellow, Magenta, BrightBlack};
+use BgColor::Redb;
+use Style::Bold;
+use paint::Paint;
+
+/// Paints of the parts of a json document
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct JsonPalette {
+    pub key: Paint,
+    pub string: Paint,
+    pub number: Paint,
+    pub boolean: Paint,
+    pub null: Paint,
+    pub punctuation: Paint,
+    pub error: Paint
+}
+
+impl JsonPalette {
+    /// The default palette
+    pub const DEFAULT: JsonPalette = JsonPalette {
+        key: Paint::new().fg(Blue).style(Bold),
+        string: Paint::new().fg(Green),
+        number: Paint::new().fg(Yellow),
+        boolean: Paint::new().fg(Magenta),
+        null: Paint::new().fg(BrightBlack),
+        punctuation: Paint::new(),
+        error: Paint::new().bg(Redb).style(Bold)
+    };
+}
+
+impl Default for JsonPalette {
+    fn default() -> JsonPalette {
+        JsonPalette::DEFAULT
+    }
+}
+
+/// Pretty print and highlight a json text with the default palette
+pub fn highlight_json(text: &str) -> String {
+    JsonPrinter::new().highlight(text)
+}
+
+/// A json pretty printer
+#[derive(Clone, Copy, Debug)]
+pub struct JsonPrinter {
+    palette: JsonPalette,
+    indent: usize
+}
+
+impl Default for JsonPrinter {
+    fn default() -> JsonPrinter {
+        JsonPrinter::new()
+    }
+}
+
+impl JsonPrinter {
+    /// Create a printer using the default palette and an indent of 2
+    pub fn new() -> JsonPrinter {
+        JsonPrinter { palette: JsonPalette::DEFAULT, indent: 2 }
+    }
+
+    /// Set the palette
+    pub fn palette(mut self, palette: JsonPalette) -> JsonPrinter {
+        self.palette = palette;
+        self
+    }
+
+    /// Set the number of spaces of each indentation level
+    pub fn indent(mut self, indent: usize) -> JsonPrinter {
+        self.indent = indent;
+        self
+    }
+
+    /// Pretty print and highlight a json text
+    pub fn highlight(&self, text: &str) -> String {
+        let mut parser = Parser::new(Slice { bytes: text.as_bytes(), pos: 0 }, self);
+        match parser.documents() {
+            Ok(()) => parser.out,
+            Err(pos) => {
+                let rest = &text[pos..];
+                let (bad, rest) = match rest.chars().next() {
+                    Some(c) => rest.split_at(c.len_utf8()),
+                    None => ("␄", "")
+                };
+                format!("{}{}{}", &text[..pos], self.palette.error.paint(String::from(bad)), rest)
+            }
+        }
+    }
+
+    /// Pretty print and highlight a stream of json documents
+    ///
+    /// Each document is written once it is complete. After an invalid
+    /// document the rest of the input is copied unchanged.
+    pub fn highlight_stream<R: Read, W: Write>(&self, input: R, mut output: W) -> io::Result<()> {
+        let reader = Reader { inner: BufReader::new(input), raw: Vec::new(), error: None };
+        let mut parser = Parser::new(reader, self);
+        loop {
+            parser.ws();
+            if parser.src.peek().is_none() {
+                break;
+            }
+            let result = parser.document();
+            if let Some(e) = parser.src.error.take() {
+                return Err(e);
+            }
+            if result.is_err() {
+                output.write_all(&parser.src.raw)?;
+                let mut bad = Vec::new();
+                if let Some(first) = parser.src.bump() {
+                    bad.push(first);
+                    let len = match first { 0xf0..=0xff => 4, 0xe0..=0xef => 3, 0xc0..=0xdf => 2, _ => 1 };
+                    for _ in 1..len {
+                        bad.extend(parser.src.bump());
+                    }
+                }
+                let bad = if bad.is_empty() { String::from("␄") } else { String::from_utf8_lossy(&bad).into_owned() };
+                output.write_all(self.palette.error.paint(bad).as_bytes())?;
+                io::copy(&mut parser.src.inner, &mut output)?;
+                return Ok(());
+            }
+            output.write_all(parser.out.as_bytes())?;
+            parser.out.clear();
+            parser.src.raw.clear();
+        }
+        match parser.src.error.take() {
+            Some(e) => Err(e),
+            None => Ok(())
+        }
+    }
+}
+
+// Bytes of a json text, consumed one at a time
+trait Source {
+    fn peek(&mut self) -> Option<u8>;
+    fn bump(&mut self) -> Option<u8>;
+}
+
+struct Slice<'a> {
+    bytes: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Source for Slice<'a> {
+    fn peek(&mut self) -> Option<u8> {
+        self.bytes.get(self.pos).cloned()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = self.peek();
+        self.pos += 1;
+        b
+    }
+}
+
+struct Reader<R> {
+    inner: BufReader<R>,
+    // bytes of the current document
+    raw: Vec<u8>,
+    error: Option<io::Error>
+}
+
+impl<R: Read> Source for Reader<R> {
+    fn peek(&mut self) -> Option<u8> {
+        loop {
+            match self.inner.fill_buf() {
+                Ok(buf) => return buf.first().cloned(),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = self.peek();
+        if let Some(b) = b {
+            self.inner.consume(1);
+            self.raw.push(b);
+        }
+        b
+    }
+}
+
+// Deeper documents are invalid, to bound the recursion of the parser
+const MAX_DEPTH: usize = 128;
+
+// The error is the position of the next byte of the input
+type Result = ::std::result::Result<(), ()>;
+
+struct Parser<'p, S> {
+    src: S,
+    printer: &'p JsonPrinter,
+    out: String
+}
+
+impl<'p, S: Source> Parser<'p, S> {
+    fn new(src: S, printer: &'p JsonPrinter) -> Parser<'p, S> {
+        Parser { src, printer, out: String::new() }
+    }
+
+    fn ws(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.src.peek() {
+            self.src.bump();
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result {
+        if self.src.peek() == Some(b) {
+            self.src.bump();
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn push(&mut self, paint: Paint, text: String) {
+        self.out.push_str(&paint.paint(text));
+    }
+
+    fn newline(&mut self, depth: usize) {
+        self.out.push('\n');
+        self.out.push_str(&" ".repeat(self.printer.indent * depth));
+    }
+
+    fn documents(&mut self) -> ::std::result::Result<(), usize> where S: Position {
+        loop {
+            self.ws();
+            if self.src.peek().is_none() {
+                return Ok(());
+            }
+            self.document().map_err(|_| self.src.position())?;
+        }
+    }
+
+    fn document(&mut self) -> Result {
+        self.value(0)?;
+        self.out.push('\n');
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result {
+        self.ws();
+        let palette = self.printer.palette;
+        match self.src.peek() {
+            Some(b'{') => self.container(depth, b'{', b'}'),
+            Some(b'[') => self.container(depth, b'[', b']'),
+            Some(b'"') => {
+                let s = self.string()?;
+                self.push(palette.string, s);
+                Ok(())
+            }
+            Some(b'-') | Some(b'0'..=b'9') => {
+                let n = self.number()?;
+                self.push(palette.number, n);
+                Ok(())
+            }
+            Some(b't') => self.literal("true", palette.boolean),
+            Some(b'f') => self.literal("false", palette.boolean),
+            Some(b'n') => self.literal("null", palette.null),
+            _ => Err(())
+        }
+    }
+
+    fn literal(&mut self, word: &str, paint: Paint) -> Result {
+        for &b in word.as_bytes() {
+            self.expect(b)?;
+        }
+        self.push(paint, String::from(word));
+        Ok(())
+    }
+
+    // An object or an array, with one member per line
+    fn container(&mut self, depth: usize, open: u8, close: u8) -> Result {
+        if depth >= MAX_DEPTH {
+            return Err(());
+        }
+        let punctuation = self.printer.palette.punctuation;
+        self.src.bump();
+        self.ws();
+        if self.src.peek() == Some(close) {
+            self.src.bump();
+            self.push(punctuation, format!("{}{}", open as char, close as char));
+            return Ok(());
+        }
+        self.push(punctuation, (open as char).to_string());
+        loop {
+            self.ws();
+            self.newline(depth + 1);
+            if open == b'{' {
+                if self.src.peek() != Some(b'"') {
+                    return Err(());
+                }
+                let key = self.string()?;
+                self.push(self.printer.palette.key, key);
+                self.ws();
+                self.expect(b':')?;
+                self.push(punctuation, String::from(":"));
+                self.out.push(' ');
+            }
+            self.value(depth + 1)?;
+            self.ws();
+            match self.src.peek() {
+                Some(b',') => {
+                    self.src.bump();
+                    self.push(punctuation, String::from(","));
+                }
+                Some(b) if b == close => {
+                    self.src.bump();
+                    self.newline(depth);
+                    self.push(punctuation, (close as char).to_string());
+                    return Ok(());
+                }
+                _ => return Err(())
+            }
+        }
+    }
+
+    fn string(&mut self) -> ::std::result::Result<String, ()> {
+        let mut bytes = vec![b'"'];
+        self.src.bump();
+        loop {
+            match self.src.peek() {
+                None => return Err(()),
+                Some(b) if b < 0x20 => return Err(()),
+                Some(b'"') => {
+                    bytes.extend(self.src.bump());
+                    break;
+                }
+                Some(b'\\') => {
+                    bytes.extend(self.src.bump());
+                    match self.src.peek() {
+                        Some(b'"') | Some(b'\\') | Some(b'/') | Some(b'b') | Some(b'f')
+                        | Some(b'n') | Some(b'r') | Some(b't') => bytes.extend(self.src.bump()),
+                        Some(b'u') => {
+                            bytes.extend(self.src.bump());
+                            for _ in 0..4 {
+                                match self.src.peek() {
+                                    Some(b) if (b as char).is_ascii_hexdigit() => {
+                                        bytes.extend(self.src.bump())
+                                    }
+                                    _ => return Err(())
+                                }
+                            }
+                        }
+                        _ => return Err(())
+                    }
+                }
+                Some(_) => bytes.extend(self.src.bump())
+            }
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    fn digits(&mut self, bytes: &mut Vec<u8>) -> Result {
+        let start = bytes.len();
+        while let Some(b'0'..=b'9') = self.src.peek() {
+            bytes.extend(self.src.bump());
+        }
+        if bytes.len() == start { Err(()) } else { Ok(()) }
+    }
+
+    fn number(&mut self) -> ::std::result::Result<String, ()> {
+        let mut bytes = Vec::new();
+        if self.src.peek() == Some(b'-') {
+            bytes.extend(self.src.bump());
+        }
+        if self.src.peek() == Some(b'0') {
+            bytes.extend(self.src.bump());
+        } else {
+            self.digits(&mut bytes)?;
+        }
+        if self.src.peek() == Some(b'.') {
+            bytes.extend(self.src.bump());
+            self.digits(&mut bytes)?;
+        }
+        if let Some(b'e') | Some(b'E') = self.src.peek() {
+            bytes.extend(self.src.bump());
+            if let Some(b'+') | Some(b'-') = self.src.peek() {
+                bytes.extend(self.src.bump());
+            }
+            self.digits(&mut bytes)?;
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+// Sources knowing the position of their next byte
+trait Position {
+    fn position(&self) -> usize;
+}
+
+impl<'a> Position for Slice<'a> {
+    fn position(&self) -> usize {
+        self.pos
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonPalette, JsonPrinter, MAX_DEPTH};
+    use ansi;
+
+    fn error(bad: &str) -> String {
+        JsonPalette::DEFAULT.error.paint(String::from(bad))
+    }
+
+    #[test]
+    fn pretty_print() {
+        let out = JsonPrinter::new().highlight(r#"{"a": [1, -2.5e3, true, null], "b" : {}, "c": "x\"y"}"#);
+        assert_eq!(ansi::strip(&out), "{\n  \"a\": [\n    1,\n    -2.5e3,\n    true,\n    null\n  ],\n  \
+                                       \"b\": {},\n  \"c\": \"x\\\"y\"\n}\n");
+        assert!(out.contains(&JsonPalette::DEFAULT.key.paint(String::from("\"a\""))));
+        let out = JsonPrinter::new().indent(4).highlight("[1]\n[2]");
+        assert_eq!(ansi::strip(&out), "[\n    1\n]\n[\n    2\n]\n");
+    }
+
+    #[test]
+    fn invalid_input() {
+        let printer = JsonPrinter::new();
+        assert_eq!(printer.highlight(r#"{"a" 1}"#), format!("{{\"a\" {}}}", error("1")));
+        assert_eq!(printer.highlight("[1, é]"), format!("[1, {}]", error("é")));
+        assert_eq!(printer.highlight("[1,"), format!("[1,{}", error("␄")));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let deep = "[".repeat(200000) + &"]".repeat(200000);
+        let out = JsonPrinter::new().highlight(&deep);
+        assert_eq!(&out[..MAX_DEPTH], &deep[..MAX_DEPTH]);
+        assert_eq!(ansi::strip(&out), deep);
+        let ok = "[".repeat(MAX_DEPTH) + &"]".repeat(MAX_DEPTH);
+        assert_eq!(ansi::strip(&JsonPrinter::new().indent(0).highlight(&ok)).matches('[').count(), MAX_DEPTH);
+    }
+
+    #[test]
+    fn stream() {
+        let mut out = Vec::new();
+        JsonPrinter::new().highlight_stream(&b"{\"a\":1} [true]\n[1 2] {}"[..], &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.ends_with(&format!("[1 {}] {{}}", error("2"))));
+        // the invalid document is copied with the spaces before it
+        assert_eq!(ansi::strip(&out), "{\n  \"a\": 1\n}\n[\n  true\n]\n\n[1 2] {}");
+    }
+}
@@ -0,0 +1,196 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Logger for the `log` crate with colored levels, enabled by the `log` feature.
+//
+// ```Rust
+// colorize::logger::Logger::new()
+//     .level(LevelFilter::Debug)
+//     .color(Level::Info, Cyan)
+//     .init()
+//     .unwrap();
+// ```
+
+use std::io::{self, Write};
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use Color;
+use Color::{BrightRed, Yellow, Green, Blue, BrightBlack};
+use Style::Faint;
+use ansi;
+use internal;
+use term::{self, Stream};
+use time::timestamp;
+
+/// A logger writing colored records to stdout or stderr
+pub struct Logger {
+    level: LevelFilter,
+    // indexed by level, from Error to Trace
+    colors: [Color; 5],
+    stream: Stream,
+    timestamp: bool
+}
+
+impl Default for Logger {
+    fn default() -> Logger {
+        Logger::new()
+    }
+}
+
+impl Logger {
+    /// Create a logger writing records up to `Info` on stderr
+    pub fn new() -> Logger {
+        Logger {
+            level: LevelFilter::Info,
+            colors: [BrightRed, Yellow, Green, Blue, BrightBlack],
+            stream: Stream::Stderr,
+            timestamp: true
+        }
+    }
+
+    /// Set the most verbose level written
+    pub fn level(mut self, level: LevelFilter) -> Logger {
+        self.level = level;
+        self
+    }
+
+    /// Set the color of a level
+    pub fn color(mut self, level: Level, color: Color) -> Logger {
+        self.colors[level as usize - 1] = color;
+        self
+    }
+
+    /// Set the stream the records are written to
+    pub fn stream(mut self, stream: Stream) -> Logger {
+        self.stream = stream;
+        self
+    }
+
+    /// Write the time of each record
+    pub fn timestamp(mut self, timestamp: bool) -> Logger {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Install the logger as the logger of the `log` crate
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        ::log::set_max_level(self.level);
+        ::log::set_boxed_logger(Box::new(self))
+    }
+
+    /// Format a record on a single line, with or without colors
+    ///
+    /// Without colors the escapes of the message are removed as well.
+    pub fn format(&self, record: &Record, colored: bool) -> String {
+        let dim = |s: String| if colored { internal::pack(Faint, s) } else { s };
+        let mut line = String::new();
+        if self.timestamp {
+            line.push_str(&dim(timestamp()));
+            line.push(' ');
+        }
+        let level = format!("{:<5}", record.level());
+        if colored {
+            line.push_str(&internal::pack(self.colors[record.level() as usize - 1], level));
+        } else {
+            line.push_str(&level);
+        }
+        line.push(' ');
+        line.push_str(&dim(format!("{}:", record.target())));
+        let message = record.args().to_string();
+        line.push(' ');
+        if colored {
+            line.push_str(&message);
+        } else {
+            // messages built with colors stay clean in log files
+            line.push_str(&ansi::strip(&message));
+        }
+        line
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = self.format(record, term::colors_enabled(self.stream));
+        let _ = match self.stream {
+            Stream::Stdout => writeln!(io::stdout(), "{}", line),
+            Stream::Stderr => writeln!(io::stderr(), "{}", line)
+        };
+    }
+
+    fn flush(&self) {
+        let _ = match self.stream {
+            Stream::Stdout => io::stdout().flush(),
+            Stream::Stderr => io::stderr().flush()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::{Level, Record};
+
+    use super::Logger;
+    use Color::{Cyan, Yellow};
+    use Style::Faint;
+    use internal;
+
+    fn format(logger: &Logger, level: Level, message: &str, colored: bool) -> String {
+        logger.format(&Record::builder()
+                      .level(level)
+                      .target("app")
+                      .args(format_args!("{}", message))
+                      .build(), colored)
+    }
+
+    #[test]
+    fn uncolored() {
+        let logger = Logger::new().timestamp(false);
+        assert_eq!(format(&logger, Level::Warn, "disk \x1b[31mfull\x1b[0m", false), "WARN  app: disk full");
+    }
+
+    #[test]
+    fn level_colors() {
+        let logger = Logger::new().timestamp(false).color(Level::Info, Cyan);
+        let expected = format!("{} {} hi", internal::pack(Yellow, "WARN ".to_string()),
+                               internal::pack(Faint, "app:".to_string()));
+        assert_eq!(format(&logger, Level::Warn, "hi", true), expected);
+        let expected = format!("{} {} hi", internal::pack(Cyan, "INFO ".to_string()),
+                               internal::pack(Faint, "app:".to_string()));
+        assert_eq!(format(&logger, Level::Info, "hi", true), expected);
+    }
+
+    #[test]
+    fn timestamp() {
+        let line = format(&Logger::new(), Level::Error, "hi", false);
+        let (time, rest) = line.split_at(24);
+        assert_eq!(rest, " ERROR app: hi");
+        assert!(time.ends_with('Z') && time.as_bytes()[10] == b'T');
+        assert!(!format(&Logger::new().timestamp(false), Level::Error, "hi", false).contains('Z'));
+    }
+}
@@ -0,0 +1,171 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Terminal multiplexers, and passing sequences through them to the terminal.
+//
+// ```Rust
+// colorize::mux::set_passthrough(true);
+// // wrapped in a DCS passthrough when run inside tmux or screen
+// print!("{}", Osc::Title("build"));
+// ```
+
+use std::borrow::Cow;
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ansi;
+
+/// A terminal multiplexer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Multiplexer {
+    Tmux,
+    Screen
+}
+
+// GNU screen drops longer passthrough strings
+const SCREEN_CHUNK: usize = 768;
+
+impl Multiplexer {
+    /// The multiplexer the program runs in, from `TMUX` or `STY`
+    pub fn detect() -> Option<Multiplexer> {
+        let set = |name| env::var_os(name).is_some_and(|v| !v.is_empty());
+        if set("TMUX") {
+            Some(Multiplexer::Tmux)
+        } else if set("STY") {
+            Some(Multiplexer::Screen)
+        } else {
+            None
+        }
+    }
+
+    /// Wrap a sequence so the multiplexer sends it as is to the terminal
+    ///
+    /// tmux needs `set -g allow-passthrough on`. Sequences are cut in
+    /// chunks for screen, whose strings cannot hold ST, so a final ST is
+    /// replaced by BEL.
+    pub fn wrap(&self, seq: &str) -> String {
+        match *self {
+            Multiplexer::Tmux => {
+                let mut out = String::from("\x1bPtmux;");
+                // inner escapes are doubled
+                out.push_str(&seq.replace('\x1b', "\x1b\x1b"));
+                out.push_str("\x1b\\");
+                out
+            }
+            Multiplexer::Screen => {
+                let seq = match seq.strip_suffix("\x1b\\") {
+                    Some(body) => Cow::Owned(format!("{}\x07", body)),
+                    None => Cow::Borrowed(seq)
+                };
+                let mut out = String::new();
+                let mut rest = &seq[..];
+                while !rest.is_empty() {
+                    let mut end = rest.len().min(SCREEN_CHUNK);
+                    while !rest.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    out.push_str("\x1bP");
+                    out.push_str(&rest[..end]);
+                    out.push_str("\x1b\\");
+                    rest = &rest[end..];
+                }
+                out
+            }
+        }
+    }
+}
+
+static PASSTHROUGH: AtomicBool = AtomicBool::new(false);
+
+/// Wrap the sequences written by the library in a multiplexer passthrough
+///
+/// Applies to operating system commands, palette changes and queries, and
+/// only inside tmux or screen. Colors and styles are never wrapped.
+pub fn set_passthrough(enabled: bool) {
+    PASSTHROUGH.store(enabled, Ordering::Relaxed)
+}
+
+/// Is the passthrough enabled
+pub fn passthrough_enabled() -> bool {
+    PASSTHROUGH.load(Ordering::Relaxed)
+}
+
+/// Wrap a sequence if the passthrough is enabled inside a multiplexer
+///
+/// Sgr sequences are returned unchanged, the multiplexer handles them.
+pub fn passthrough(seq: &str) -> Cow<'_, str> {
+    if !passthrough_enabled() {
+        return Cow::Borrowed(seq);
+    }
+    wrap_in(Multiplexer::detect(), seq)
+}
+
+fn wrap_in(mux: Option<Multiplexer>, seq: &str) -> Cow<'_, str> {
+    match mux {
+        Some(mux) if !ansi::is_sgr(seq) => Cow::Owned(mux.wrap(seq)),
+        _ => Cow::Borrowed(seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::{passthrough, passthrough_enabled, wrap_in, Multiplexer, SCREEN_CHUNK};
+
+    #[test]
+    fn tmux() {
+        assert_eq!(Multiplexer::Tmux.wrap("\x1b]2;title\x07"), "\x1bPtmux;\x1b\x1b]2;title\x07\x1b\\");
+        assert_eq!(Multiplexer::Tmux.wrap("\x1b]8;;url\x1b\\"), "\x1bPtmux;\x1b\x1b]8;;url\x1b\x1b\\\x1b\\");
+    }
+
+    #[test]
+    fn screen() {
+        assert_eq!(Multiplexer::Screen.wrap("\x1b]2;title\x07"), "\x1bP\x1b]2;title\x07\x1b\\");
+        // the final ST becomes BEL, the sequence is cut in chunks
+        let body = format!("\x1b]2;{}", "x".repeat(2000));
+        let wrapped = Multiplexer::Screen.wrap(&format!("{}\x1b\\", body));
+        let chunks: Vec<&str> = wrapped.split("\x1b\\").filter(|c| !c.is_empty()).collect();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.starts_with("\x1bP") && c.len() <= SCREEN_CHUNK + 2));
+        assert_eq!(chunks[0].len(), SCREEN_CHUNK + 2);
+        let joined: String = chunks.iter().map(|c| &c[2..]).collect();
+        assert_eq!(joined, format!("{}\x07", body));
+    }
+
+    #[test]
+    fn screen_char_boundaries() {
+        let seq = format!("{}é", "x".repeat(SCREEN_CHUNK - 1));
+        let wrapped = Multiplexer::Screen.wrap(&seq);
+        assert_eq!(wrapped, format!("\x1bP{}\x1b\\\x1bPé\x1b\\", "x".repeat(SCREEN_CHUNK - 1)));
+    }
+
+    #[test]
+    fn unwrapped() {
+        let osc = "\x1b]2;title\x07";
+        assert!(matches!(wrap_in(None, osc), Cow::Borrowed(s) if s == osc));
+        assert!(matches!(wrap_in(Some(Multiplexer::Tmux), "\x1b[1;31m"), Cow::Borrowed("\x1b[1;31m")));
+        assert_eq!(wrap_in(Some(Multiplexer::Screen), osc), Multiplexer::Screen.wrap(osc));
+        if !passthrough_enabled() {
+            assert!(matches!(passthrough(osc), Cow::Borrowed(s) if s == osc));
+        }
+    }
+}
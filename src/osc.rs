@@ -0,0 +1,173 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Operating system commands: window title, notifications, working directory
+// and hyperlinks.
+//
+// ```Rust
+// print!("{}", Osc::Notify("build finished"));
+// print!("{}", Osc::Hyperlink { url: "https://example.com", text: "docs" });
+// let title = TitleGuard::new(io::stdout(), "building...")?;
+// ```
+
+use std::fmt;
+use std::io::{self, Write};
+use std::path::Path;
+
+use mux;
+
+/// Save the window title on the terminal stack, CSI 22 t
+pub const PUSH_TITLE: &str = "\x1b[22;0t";
+
+/// Restore the last saved window title, CSI 23 t
+pub const POP_TITLE: &str = "\x1b[23;0t";
+
+/// An operating system command
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Osc<'a> {
+    /// Set the window title, OSC 2
+    Title(&'a str),
+    /// Set the window title and the icon name, OSC 0
+    IconAndTitle(&'a str),
+    /// Post a desktop notification, OSC 9
+    Notify(&'a str),
+    /// Post a desktop notification with a title, OSC 777
+    NotifyWithTitle { title: &'a str, body: &'a str },
+    /// Report the working directory, OSC 7
+    WorkingDirectory { host: &'a str, path: &'a Path },
+    /// A text linking to an url, OSC 8
+    Hyperlink { url: &'a str, text: &'a str }
+}
+
+// Control characters would end the command early
+fn sanitize(text: &str) -> String {
+    text.chars().filter(|c| !c.is_control()).collect()
+}
+
+// Percent-encode a path for a file url
+fn encode(path: &Path) -> String {
+    let mut url = String::new();
+    for &b in path.to_string_lossy().as_bytes() {
+        match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'.' | b'_' | b'~' => url.push(b as char),
+            _ => url.push_str(&format!("%{:02X}", b))
+        }
+    }
+    url
+}
+
+impl<'a> fmt::Display for Osc<'a> {
+    /// Wrapped in a passthrough inside a multiplexer, see `mux::set_passthrough`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let seq = match *self {
+            Osc::Title(title) => format!("\x1b]2;{}\x07", sanitize(title)),
+            Osc::IconAndTitle(title) => format!("\x1b]0;{}\x07", sanitize(title)),
+            Osc::Notify(body) => format!("\x1b]9;{}\x07", sanitize(body)),
+            Osc::NotifyWithTitle { title, body } => {
+                // the title cannot hold the field separator
+                format!("\x1b]777;notify;{};{}\x07", sanitize(title).replace(';', ","), sanitize(body))
+            }
+            Osc::WorkingDirectory { host, path } => {
+                format!("\x1b]7;file://{}{}\x07", sanitize(host), encode(path))
+            }
+            Osc::Hyperlink { url, text } => {
+                // the text stays out of the passthrough, the multiplexer draws it
+                let (open, close) = (format!("\x1b]8;;{}\x1b\\", sanitize(url)), "\x1b]8;;\x1b\\");
+                return write!(f, "{}{}{}", mux::passthrough(&open), text, mux::passthrough(close));
+            }
+        };
+        f.write_str(&mux::passthrough(&seq))
+    }
+}
+
+/// A window title restored when the guard is dropped
+///
+/// The previous title is saved on the terminal stack, which is popped on
+/// drop. Terminals without a title stack keep the last title set.
+pub struct TitleGuard<W: Write> {
+    out: W
+}
+
+impl<W: Write> TitleGuard<W> {
+    /// Save the window title and set a new one
+    pub fn new(mut out: W, title: &str) -> io::Result<TitleGuard<W>> {
+        write!(out, "{}{}", mux::passthrough(PUSH_TITLE), Osc::Title(title))?;
+        out.flush()?;
+        Ok(TitleGuard { out })
+    }
+
+    /// Change the title, the saved one is still restored on drop
+    pub fn set(&mut self, title: &str) -> io::Result<()> {
+        write!(self.out, "{}", Osc::Title(title))?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for TitleGuard<W> {
+    fn drop(&mut self) {
+        let _ = self.out.write_all(mux::passthrough(POP_TITLE).as_bytes());
+        let _ = self.out.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Osc, TitleGuard};
+
+    #[test]
+    fn titles() {
+        assert_eq!(Osc::Title("build").to_string(), "\x1b]2;build\x07");
+        assert_eq!(Osc::IconAndTitle("build").to_string(), "\x1b]0;build\x07");
+        // a control character would end the command
+        assert_eq!(Osc::Title("a\x07b\x1b\\c").to_string(), "\x1b]2;ab\\c\x07");
+    }
+
+    #[test]
+    fn notifications() {
+        assert_eq!(Osc::Notify("done").to_string(), "\x1b]9;done\x07");
+        assert_eq!(Osc::NotifyWithTitle { title: "a;b", body: "c;d" }.to_string(),
+                   "\x1b]777;notify;a,b;c;d\x07");
+    }
+
+    #[test]
+    fn working_directory() {
+        let osc = Osc::WorkingDirectory { host: "box", path: Path::new("/tmp/a b") };
+        assert_eq!(osc.to_string(), "\x1b]7;file://box/tmp/a%20b\x07");
+    }
+
+    #[test]
+    fn hyperlinks_end_with_st() {
+        let osc = Osc::Hyperlink { url: "https://example.com", text: "docs" };
+        assert_eq!(osc.to_string(), "\x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\");
+    }
+
+    #[test]
+    fn title_guard() {
+        let mut out = Vec::new();
+        {
+            let mut guard = TitleGuard::new(&mut out, "one").unwrap();
+            guard.set("two").unwrap();
+        }
+        assert_eq!(out, b"\x1b[22;0t\x1b]2;one\x07\x1b]2;two\x07\x1b[23;0t");
+    }
+}
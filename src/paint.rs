@@ -0,0 +1,377 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Colors and styles applied together, usable in constants.
+//
+// ```Rust
+// const ERROR: Paint = Paint::new().fg(BrightRed).bold();
+// const ERROR_SGR: &str = ERROR.sgr().as_str();
+// println!("{}", ERROR.paint(String::from("failed")));
+// ```
+
+use core::fmt;
+use core::str;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use {Color, BgColor, Style};
+use Color::*;
+use BgColor::*;
+use ansi::{self, SgrParam};
+use internal;
+
+/// A color of a paint, one of the 16 ansi colors or a 24 bits color
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Ink<C> {
+    Ansi(C),
+    Rgb(u8, u8, u8)
+}
+
+/// Colors and styles to apply to a text
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Paint {
+    fg: Option<Ink<Color>>,
+    bg: Option<Ink<BgColor>>,
+    // bit n is set for the style of code n
+    styles: u16
+}
+
+impl Paint {
+    /// A paint leaving the text unchanged
+    pub const fn new() -> Paint {
+        Paint { fg: None, bg: None, styles: 0 }
+    }
+
+    /// Set the foreground color
+    pub const fn fg(mut self, color: Color) -> Paint {
+        self.fg = Some(Ink::Ansi(color));
+        self
+    }
+
+    /// Set the background color
+    pub const fn bg(mut self, color: BgColor) -> Paint {
+        self.bg = Some(Ink::Ansi(color));
+        self
+    }
+
+    /// Set a 24 bits foreground color
+    pub const fn fg_rgb(mut self, r: u8, g: u8, b: u8) -> Paint {
+        self.fg = Some(Ink::Rgb(r, g, b));
+        self
+    }
+
+    /// Set a 24 bits background color
+    pub const fn bg_rgb(mut self, r: u8, g: u8, b: u8) -> Paint {
+        self.bg = Some(Ink::Rgb(r, g, b));
+        self
+    }
+
+    /// Add a style
+    pub const fn style(mut self, style: Style) -> Paint {
+        self.styles |= 1 << (style as u16);
+        self
+    }
+
+    /// Remove a style
+    pub const fn without(mut self, style: Style) -> Paint {
+        self.styles &= !(1 << (style as u16));
+        self
+    }
+
+    /// Go back to the default foreground color
+    pub const fn clear_fg(mut self) -> Paint {
+        self.fg = None;
+        self
+    }
+
+    /// Go back to the default background color
+    pub const fn clear_bg(mut self) -> Paint {
+        self.bg = None;
+        self
+    }
+
+    /// Bold
+    pub const fn bold(self) -> Paint { self.style(Style::Bold) }
+    /// Faint
+    pub const fn faint(self) -> Paint { self.style(Style::Faint) }
+    /// Italic
+    pub const fn italic(self) -> Paint { self.style(Style::Italic) }
+    /// Underlined
+    pub const fn underlined(self) -> Paint { self.style(Style::Underscore) }
+    /// Blink
+    pub const fn blink(self) -> Paint { self.style(Style::Blink) }
+    /// Reverse
+    pub const fn reverse(self) -> Paint { self.style(Style::Reverse) }
+    /// Concealed
+    pub const fn concealed(self) -> Paint { self.style(Style::Concealed) }
+    /// Crossed out
+    pub const fn crossedout(self) -> Paint { self.style(Style::CrossedOut) }
+
+    /// The foreground color
+    pub const fn foreground(&self) -> Option<Ink<Color>> {
+        self.fg
+    }
+
+    /// The background color
+    pub const fn background(&self) -> Option<Ink<BgColor>> {
+        self.bg
+    }
+
+    /// Is a style set
+    pub const fn has_style(&self, style: Style) -> bool {
+        self.styles & (1 << (style as u16)) != 0
+    }
+
+    /// Does the paint change anything
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && self.styles == 0
+    }
+
+    /// The escape sequence setting the colors and styles
+    ///
+    /// Empty for a plain paint. Unlike `paint`, it ignores the global colors
+    /// and the cvd safe mode, and 24 bits colors are always written as is.
+    pub const fn sgr(&self) -> Sgr {
+        let mut sgr = Sgr { buf: [0; SGR_LEN], len: 0 };
+        if self.is_plain() {
+            return sgr;
+        }
+        // same order as the sequence written by `paint`
+        sgr = sgr.push(b'\x1b').push(b'[');
+        match self.fg {
+            Some(Ink::Ansi(fg)) => sgr = sgr.push_code(fg as u16),
+            Some(Ink::Rgb(r, g, b)) => sgr = sgr.push_rgb(38, r, g, b),
+            None => {}
+        }
+        match self.bg {
+            Some(Ink::Ansi(bg)) => sgr = sgr.push_code(bg as u16),
+            Some(Ink::Rgb(r, g, b)) => sgr = sgr.push_rgb(48, r, g, b),
+            None => {}
+        }
+        let mut code = 1;
+        while code < 10 {
+            if self.styles & (1 << code) != 0 {
+                sgr = sgr.push_code(code);
+            }
+            code += 1;
+        }
+        // replace the last separator
+        sgr.len -= 1;
+        sgr.push(b'm')
+    }
+
+    /// Write a text with the colors and the styles, followed by a reset
+    ///
+    /// Works with any `fmt::Write`, such as a serial port writer.
+    pub fn write<W: fmt::Write>(&self, out: &mut W, text: &str) -> fmt::Result {
+        if self.is_plain() {
+            return out.write_str(text);
+        }
+        out.write_str(self.sgr().as_str())?;
+        out.write_str(text)?;
+        out.write_str(RESET)
+    }
+
+    /// Update the paint with an escape sequence, as a terminal would
+    ///
+    /// Escape sequences other than colors and styles are ignored.
+    pub fn apply_sgr(mut self, escape: &str) -> Paint {
+        if !ansi::is_sgr(escape) {
+            return self;
+        }
+        for param in ansi::sgr_params(&escape[2..escape.len() - 1]) {
+            self = match param {
+                SgrParam::Reset => Paint::new(),
+                SgrParam::Style(style) => self.style(style),
+                SgrParam::NoStyle(style) => self.without(style),
+                SgrParam::Fg(Some(Ink::Ansi(i))) => self.fg(COLORS[i as usize]),
+                SgrParam::Fg(Some(Ink::Rgb(r, g, b))) => self.fg_rgb(r, g, b),
+                SgrParam::Fg(None) => self.clear_fg(),
+                SgrParam::Bg(Some(Ink::Ansi(i))) => self.bg(BG_COLORS[i as usize]),
+                SgrParam::Bg(Some(Ink::Rgb(r, g, b))) => self.bg_rgb(r, g, b),
+                SgrParam::Bg(None) => self.clear_bg()
+            };
+        }
+        self
+    }
+
+    // The codes written by `paint`, adapted to the terminal
+    fn codes(&self) -> Vec<String> {
+        let mut codes = Vec::new();
+        match self.fg {
+            Some(Ink::Ansi(fg)) => codes.extend(internal::code(fg)),
+            Some(Ink::Rgb(r, g, b)) => codes.push(rgb_code(false, r, g, b)),
+            None => {}
+        }
+        match self.bg {
+            Some(Ink::Ansi(bg)) => codes.extend(internal::code(bg)),
+            Some(Ink::Rgb(r, g, b)) => codes.push(rgb_code(true, r, g, b)),
+            None => {}
+        }
+        for &style in STYLES.iter() {
+            if self.styles & (1 << (style as u16)) != 0 {
+                codes.extend(internal::code(style));
+            }
+        }
+        codes
+    }
+
+    /// The escape sequence `paint` writes before a text
+    ///
+    /// Empty when nothing is written. Unlike `sgr`, it follows the color
+    /// depth, the terminal capabilities and the cvd safe mode.
+    pub fn prefix(&self) -> String {
+        let codes = self.codes();
+        if codes.is_empty() { String::new() } else { format!("\x1b[{}m", codes.join(";")) }
+    }
+
+    /// Apply the colors and the styles to a text
+    pub fn paint(&self, text: String) -> String {
+        let codes = self.codes();
+        if codes.is_empty() { text } else { internal::pack_code(codes.join(";"), text) }
+    }
+}
+
+#[cfg(feature = "std")]
+fn rgb_code(background: bool, r: u8, g: u8, b: u8) -> String {
+    let (rgb, depth) = (::rgb::Rgb::new(r, g, b), ::rgb::color_depth());
+    if background { ::rgb::bg_code(rgb, depth) } else { ::rgb::fg_code(rgb, depth) }
+}
+
+#[cfg(not(feature = "std"))]
+fn rgb_code(background: bool, r: u8, g: u8, b: u8) -> String {
+    format!("{};2;{};{};{}", if background { 48 } else { 38 }, r, g, b)
+}
+
+const COLORS: [Color; 16] = [
+    Black, Red, Green, Yellow, Blue, Magenta, Cyan, Grey,
+    BrightBlack, BrightRed, BrightGreen, BrightYellow, BrightBlue, BrightMagenta, BrightCyan, BrightGrey
+];
+
+const BG_COLORS: [BgColor; 16] = [
+    Blackb, Redb, Greenb, Yellowb, Blueb, Magentab, Cyanb, Greyb,
+    BrightBlackb, BrightRedb, BrightGreenb, BrightYellowb, BrightBlueb, BrightMagentab, BrightCyanb, BrightGreyb
+];
+
+const STYLES: [Style; 8] = [
+    Style::Bold, Style::Faint, Style::Italic, Style::Underscore,
+    Style::Blink, Style::Reverse, Style::Concealed, Style::CrossedOut
+];
+
+/// Reset the colors and the styles
+pub const RESET: &str = "\x1b[0m";
+
+// Escape, bracket, 8 styles, 2 rgb colors and the final byte
+const SGR_LEN: usize = 64;
+
+/// An escape sequence built at compile time, see `Paint::sgr`
+#[derive(Clone, Copy)]
+pub struct Sgr {
+    buf: [u8; SGR_LEN],
+    len: usize
+}
+
+impl Sgr {
+    const fn push(mut self, b: u8) -> Sgr {
+        self.buf[self.len] = b;
+        self.len += 1;
+        self
+    }
+
+    // Push a code followed by a separator
+    const fn push_code(mut self, code: u16) -> Sgr {
+        if code >= 100 {
+            self = self.push(b'0' + (code / 100) as u8);
+        }
+        if code >= 10 {
+            self = self.push(b'0' + (code / 10 % 10) as u8);
+        }
+        self.push(b'0' + (code % 10) as u8).push(b';')
+    }
+
+    const fn push_rgb(self, code: u16, r: u8, g: u8, b: u8) -> Sgr {
+        self.push_code(code).push_code(2).push_code(r as u16).push_code(g as u16).push_code(b as u16)
+    }
+
+    /// The escape sequence
+    pub const fn as_str(&self) -> &str {
+        let (bytes, _) = self.buf.split_at(self.len);
+        // only ascii bytes are pushed
+        unsafe { str::from_utf8_unchecked(bytes) }
+    }
+}
+
+impl fmt::Display for Sgr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Sgr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    use super::{Paint, RESET};
+    use Color::BrightRed;
+    use BgColor::Blueb;
+    use Style::{Bold, Underscore};
+
+    const ERROR: Paint = Paint::new().fg(BrightRed).bold();
+    const ERROR_SGR: &str = ERROR.sgr().as_str();
+
+    #[test]
+    fn const_sgr() {
+        assert_eq!(ERROR_SGR, "\x1b[91;1m");
+        assert_eq!(Paint::new().bg(Blueb).style(Underscore).sgr().as_str(), "\x1b[44;4m");
+        assert_eq!(Paint::new().fg_rgb(1, 2, 3).bg_rgb(255, 0, 10).sgr().as_str(),
+                   "\x1b[38;2;1;2;3;48;2;255;0;10m");
+        assert_eq!(ERROR.without(Bold).clear_fg().sgr().as_str(), "");
+    }
+
+    #[test]
+    fn write() {
+        let mut out = String::new();
+        ERROR.write(&mut out, "failed").unwrap();
+        assert_eq!(out, String::from(ERROR_SGR) + "failed" + RESET);
+    }
+
+    #[test]
+    fn plain() {
+        let plain = Paint::new();
+        assert!(plain.is_plain());
+        assert_eq!(plain.sgr().as_str(), "");
+        assert_eq!(plain.prefix(), "");
+        assert_eq!(plain.paint(String::from("text")), "text");
+        let mut out = String::new();
+        plain.write(&mut out, "text").unwrap();
+        assert_eq!(out, "text");
+        let mut out = String::new();
+        ERROR.write(&mut out, "").unwrap();
+        assert_eq!(out, String::from(ERROR_SGR) + RESET);
+    }
+}
@@ -0,0 +1,346 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Progress bar redrawn in place on terminals.
+//
+// ```Rust
+// let mut bar = ProgressBar::new(100).width(30).color(Green);
+// for _ in 0..100 {
+//     bar.inc(1);
+// }
+// bar.finish();
+// ```
+
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use {Color, BgColor};
+use internal;
+
+/// Characters used to draw a progress bar
+#[derive(Clone, Copy, Debug)]
+pub struct BarChars {
+    /// A completely filled cell
+    pub full: char,
+    /// Partially filled cells, from the least to the most filled
+    pub partial: &'static [char],
+    /// An empty cell
+    pub empty: char
+}
+
+impl BarChars {
+    /// Unicode blocks with a precision of an eighth of cell
+    pub const BLOCKS: BarChars = BarChars {
+        full: '█',
+        partial: &['▏', '▎', '▍', '▌', '▋', '▊', '▉'],
+        empty: ' '
+    };
+
+    /// Plain ascii characters
+    pub const ASCII: BarChars = BarChars { full: '#', partial: &[], empty: '-' };
+}
+
+// delay between two redraws on terminals
+const REDRAW_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A progress bar with a rate and an estimated time of arrival
+pub struct ProgressBar {
+    total: u64,
+    current: u64,
+    width: usize,
+    chars: BarChars,
+    fg: Color,
+    bg: BgColor,
+    message: String,
+    start: Instant,
+    last_draw: Option<Instant>,
+    interval: Duration,
+    tty: bool,
+    finished: bool,
+    out: Box<dyn Write + Send>
+}
+
+impl ProgressBar {
+    /// Create a progress bar going up to `total`, drawn on stderr
+    pub fn new(total: u64) -> ProgressBar {
+        let tty = io::stderr().is_terminal();
+        ProgressBar::with_writer(total, Box::new(io::stderr()), tty)
+    }
+
+    /// Create a progress bar drawn on a custom writer
+    ///
+    /// When `tty` is false the bar is not redrawn in place, a plain
+    /// text line is written periodically instead.
+    pub fn with_writer(total: u64, out: Box<dyn Write + Send>, tty: bool) -> ProgressBar {
+        ProgressBar {
+            total,
+            current: 0,
+            width: 40,
+            chars: BarChars::BLOCKS,
+            fg: Color::Green,
+            bg: BgColor::Greyb,
+            message: String::new(),
+            start: Instant::now(),
+            last_draw: None,
+            interval: Duration::from_secs(1),
+            tty,
+            finished: false,
+            out
+        }
+    }
+
+    /// Set the number of cells of the bar
+    pub fn width(mut self, width: usize) -> ProgressBar {
+        self.width = width;
+        self
+    }
+
+    /// Set the characters used to draw the bar
+    pub fn chars(mut self, chars: BarChars) -> ProgressBar {
+        self.chars = chars;
+        self
+    }
+
+    /// Set the color of the filled part of the bar
+    pub fn color(mut self, color: Color) -> ProgressBar {
+        self.fg = color;
+        self
+    }
+
+    /// Set the background of the whole bar
+    pub fn background(mut self, color: BgColor) -> ProgressBar {
+        self.bg = color;
+        self
+    }
+
+    /// Set the delay between two lines when the output is not a terminal
+    pub fn interval(mut self, interval: Duration) -> ProgressBar {
+        self.interval = interval;
+        self
+    }
+
+    /// Set the message displayed before the bar
+    pub fn set_message(&mut self, message: &str) {
+        self.message = String::from(message);
+        self.draw(false);
+    }
+
+    /// Advance the progress by `delta`
+    pub fn inc(&mut self, delta: u64) {
+        let pos = self.current.saturating_add(delta);
+        self.set_position(pos);
+    }
+
+    /// Set the current progress
+    pub fn set_position(&mut self, pos: u64) {
+        self.current = pos.min(self.total);
+        self.draw(false);
+    }
+
+    /// Get the current progress
+    pub fn position(&self) -> u64 {
+        self.current
+    }
+
+    /// Draw the bar a last time and move to the next line
+    pub fn finish(&mut self) {
+        self.draw(true);
+        if self.tty {
+            let _ = writeln!(self.out);
+        }
+        let _ = self.out.flush();
+        self.finished = true;
+    }
+
+    /// Number of items done per second
+    pub fn rate(&self) -> f64 {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        if elapsed > 0.0 { self.current as f64 / elapsed } else { 0.0 }
+    }
+
+    /// Estimated time remaining, `None` until some progress was made
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.rate();
+        if rate > 0.0 {
+            Duration::try_from_secs_f64((self.total - self.current) as f64 / rate).ok()
+        } else {
+            None
+        }
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.total == 0 { 1.0 } else { self.current as f64 / self.total as f64 }
+    }
+
+    /// Render the bar without any positioning escape sequence
+    pub fn render(&self) -> String {
+        let eighths = (self.ratio() * (self.width * 8) as f64) as usize;
+        let mut full = String::new();
+        for _ in 0..eighths / 8 {
+            full.push(self.chars.full);
+        }
+        let mut cells = eighths / 8;
+        let rest = eighths % 8;
+        let mut empty = String::new();
+        if cells < self.width && rest > 0 && !self.chars.partial.is_empty() {
+            let n = self.chars.partial.len();
+            empty.push(self.chars.partial[(rest * (n + 1) / 8).max(1) - 1]);
+            cells += 1;
+        }
+        for _ in cells..self.width {
+            empty.push(self.chars.empty);
+        }
+
+        let mut line = String::new();
+        if !self.message.is_empty() {
+            line.push_str(&self.message);
+            line.push(' ');
+        }
+        let bar = internal::pack(self.bg, internal::pack(self.fg, full + &empty));
+        line.push_str(&bar);
+        line.push_str(&format!(" {:3}% {}/{} {}",
+                               (self.ratio() * 100.0) as u32,
+                               self.current, self.total,
+                               format_rate(self.rate())));
+        if let Some(eta) = self.eta() {
+            line.push_str(&format!(" ETA {}", format_duration(eta)));
+        }
+        line
+    }
+
+    fn draw(&mut self, force: bool) {
+        let now = Instant::now();
+        let interval = if self.tty { REDRAW_INTERVAL } else { self.interval };
+        let due = self.last_draw.is_none_or(|t| now.duration_since(t) >= interval);
+        if !force && !due {
+            return;
+        }
+        if self.tty {
+            let line = self.render();
+            let _ = write!(self.out, "\r\x1b[2K{}", line);
+            let _ = self.out.flush();
+        } else {
+            let mut line = String::new();
+            if !self.message.is_empty() {
+                line.push_str(&self.message);
+                line.push(' ');
+            }
+            let _ = writeln!(self.out, "{}{}% {}/{}", line,
+                             (self.ratio() * 100.0) as u32, self.current, self.total);
+        }
+        self.last_draw = Some(now);
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        // leave the line of an unfinished bar
+        if self.tty && !self.finished && self.last_draw.is_some() {
+            let _ = writeln!(self.out);
+            let _ = self.out.flush();
+        }
+    }
+}
+
+fn format_rate(rate: f64) -> String {
+    if rate >= 100.0 {
+        format!("{:.0}/s", rate)
+    } else {
+        format!("{:.1}/s", rate)
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    use super::{BarChars, ProgressBar};
+    use ansi;
+
+    fn bar(total: u64) -> ProgressBar {
+        ProgressBar::with_writer(total, Box::new(io::sink()), false).width(4).chars(BarChars::ASCII)
+    }
+
+    #[test]
+    fn render_empty() {
+        assert!(ansi::strip(&bar(10).render()).starts_with("----   0% 0/10 "));
+    }
+
+    #[test]
+    fn render_full() {
+        let mut bar = bar(10);
+        bar.set_position(12);
+        assert!(ansi::strip(&bar.render()).starts_with("#### 100% 10/10 "));
+    }
+
+    #[test]
+    fn render_zero_total() {
+        assert!(ansi::strip(&bar(0).render()).starts_with("#### 100% 0/0 "));
+    }
+
+    #[test]
+    fn render_custom_chars() {
+        let chars = BarChars { full: '=', partial: &['a', 'b', 'c'], empty: '.' };
+        let mut bar = bar(16).width(2).chars(chars);
+        bar.set_position(3);
+        assert!(ansi::strip(&bar.render()).starts_with("a.  18% 3/16 "));
+        bar.set_position(12);
+        assert!(ansi::strip(&bar.render()).starts_with("=b  75% 12/16 "));
+    }
+
+    #[derive(Clone)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn drop_ends_the_line() {
+        let out = Shared(Arc::new(Mutex::new(Vec::new())));
+        let mut bar = ProgressBar::with_writer(10, Box::new(out.clone()), true);
+        bar.inc(1);
+        drop(bar);
+        assert!(out.0.lock().unwrap().ends_with(b"\n"));
+
+        let out = Shared(Arc::new(Mutex::new(Vec::new())));
+        let mut bar = ProgressBar::with_writer(10, Box::new(out.clone()), true);
+        bar.finish();
+        drop(bar);
+        assert!(!out.0.lock().unwrap().ends_with(b"\n\n"));
+    }
+}
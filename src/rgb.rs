@@ -0,0 +1,521 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// 24 bits colors, palettes and downsampling to what the terminal supports.
+
+use std::env;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use Color;
+use Color::*;
+use BgColor;
+use ansi;
+use paint::Ink;
+
+/// A 24 bits color
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8
+}
+
+impl Rgb {
+    /// Create a new color from its red, green and blue components
+    pub const fn new(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb { r, g, b }
+    }
+
+    /// Squared euclidean distance between two colors
+    pub fn distance(&self, other: Rgb) -> u32 {
+        let dr = self.r as i32 - other.r as i32;
+        let dg = self.g as i32 - other.g as i32;
+        let db = self.b as i32 - other.b as i32;
+        (dr * dr + dg * dg + db * db) as u32
+    }
+
+    /// Parse a color as written by terminals, `rgb:rrrr/gggg/bbbb` or `#rrggbb`
+    ///
+    /// Each component of the `rgb:` form has 1 to 4 hex digits.
+    pub fn from_x11(spec: &str) -> Option<Rgb> {
+        if let Some(hex) = spec.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return None;
+            }
+            let c = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            return Some(Rgb::new(c(0)?, c(2)?, c(4)?));
+        }
+        let spec = spec.strip_prefix("rgb:").or_else(|| spec.strip_prefix("rgba:"))?;
+        let mut parts = spec.split('/').map(|part| {
+            if part.is_empty() || part.len() > 4 {
+                return None;
+            }
+            let max = (1u32 << (4 * part.len())) - 1;
+            u32::from_str_radix(part, 16).ok().map(|v| ((v * 255 + max / 2) / max) as u8)
+        });
+        let (r, g, b) = (parts.next()??, parts.next()??, parts.next()??);
+        Some(Rgb::new(r, g, b))
+    }
+}
+
+impl From<Color> for Rgb {
+    fn from(color: Color) -> Rgb {
+        Palette::XTERM.resolve(color)
+    }
+}
+
+/// The rgb values a terminal displays for the 16 ansi colors
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Palette {
+    /// Colors in ansi order, normal colors first then the bright ones
+    pub colors: [Rgb; 16]
+}
+
+const ANSI_ORDER: [Color; 16] = [
+    Black, Red, Green, Yellow, Blue, Magenta, Cyan, Grey,
+    BrightBlack, BrightRed, BrightGreen, BrightYellow,
+    BrightBlue, BrightMagenta, BrightCyan, BrightGrey
+];
+
+impl Palette {
+    /// The xterm default palette
+    pub const XTERM: Palette = Palette { colors: [
+        Rgb::new(0, 0, 0), Rgb::new(205, 0, 0),
+        Rgb::new(0, 205, 0), Rgb::new(205, 205, 0),
+        Rgb::new(0, 0, 238), Rgb::new(205, 0, 205),
+        Rgb::new(0, 205, 205), Rgb::new(229, 229, 229),
+        Rgb::new(127, 127, 127), Rgb::new(255, 0, 0),
+        Rgb::new(0, 255, 0), Rgb::new(255, 255, 0),
+        Rgb::new(92, 92, 255), Rgb::new(255, 0, 255),
+        Rgb::new(0, 255, 255), Rgb::new(255, 255, 255)
+    ]};
+
+    /// The IBM VGA palette, used by the linux console
+    pub const VGA: Palette = Palette { colors: [
+        Rgb::new(0, 0, 0), Rgb::new(170, 0, 0),
+        Rgb::new(0, 170, 0), Rgb::new(170, 85, 0),
+        Rgb::new(0, 0, 170), Rgb::new(170, 0, 170),
+        Rgb::new(0, 170, 170), Rgb::new(170, 170, 170),
+        Rgb::new(85, 85, 85), Rgb::new(255, 85, 85),
+        Rgb::new(85, 255, 85), Rgb::new(255, 255, 85),
+        Rgb::new(85, 85, 255), Rgb::new(255, 85, 255),
+        Rgb::new(85, 255, 255), Rgb::new(255, 255, 255)
+    ]};
+
+    /// The Tango palette, used by gnome terminal
+    pub const TANGO: Palette = Palette { colors: [
+        Rgb::new(46, 52, 54), Rgb::new(204, 0, 0),
+        Rgb::new(78, 154, 6), Rgb::new(196, 160, 0),
+        Rgb::new(52, 101, 164), Rgb::new(117, 80, 123),
+        Rgb::new(6, 152, 154), Rgb::new(211, 215, 207),
+        Rgb::new(85, 87, 83), Rgb::new(239, 41, 41),
+        Rgb::new(138, 226, 52), Rgb::new(252, 233, 79),
+        Rgb::new(114, 159, 207), Rgb::new(173, 127, 168),
+        Rgb::new(52, 226, 226), Rgb::new(238, 238, 236)
+    ]};
+
+    /// Get a palette by name, `xterm`, `vga` or `tango`
+    pub fn named(name: &str) -> Option<Palette> {
+        match name {
+            "xterm" => Some(Palette::XTERM),
+            "vga" => Some(Palette::VGA),
+            "tango" => Some(Palette::TANGO),
+            _ => None
+        }
+    }
+
+    /// Get the rgb value of a color of the 256 colors table
+    ///
+    /// The first 16 colors come from the palette.
+    pub fn resolve256(&self, index: u8) -> Rgb {
+        ansi::rgb256(index).map_or(self.colors[index as usize % 16], |(r, g, b)| Rgb::new(r, g, b))
+    }
+
+    /// Get the rgb value of a color read from a sgr sequence
+    pub fn resolve_ink(&self, ink: Ink<u8>) -> Rgb {
+        match ink {
+            Ink::Ansi(index) => self.colors[index as usize % 16],
+            Ink::Rgb(r, g, b) => Rgb::new(r, g, b)
+        }
+    }
+
+    /// Get the rgb value of a color, `Default` is resolved as `Grey`
+    pub fn resolve(&self, color: Color) -> Rgb {
+        self.colors[ansi_index(color).unwrap_or(7)]
+    }
+
+    /// Get the rgb value of a background color, `Defaultb` is resolved as `Black`
+    pub fn resolve_bg(&self, color: BgColor) -> Rgb {
+        match color as i32 {
+            c @ 40..=47 => self.colors[(c - 40) as usize],
+            c @ 100..=107 => self.colors[(c - 100 + 8) as usize],
+            _ => self.colors[0]
+        }
+    }
+
+    /// Get the color of the palette closest to an rgb value
+    pub fn nearest(&self, rgb: Rgb) -> Color {
+        let mut best = 0;
+        for (i, c) in self.colors.iter().enumerate() {
+            if c.distance(rgb) < self.colors[best].distance(rgb) {
+                best = i;
+            }
+        }
+        ANSI_ORDER[best]
+    }
+}
+
+impl FromStr for Palette {
+    type Err = String;
+
+    /// Parse a palette name, or 16 colors separated by commas
+    fn from_str(s: &str) -> Result<Palette, String> {
+        if let Some(palette) = Palette::named(s) {
+            return Ok(palette);
+        }
+        let colors = s.split(',').map(|c| Rgb::from_x11(c.trim())).collect::<Option<Vec<Rgb>>>();
+        match colors {
+            Some(ref colors) if colors.len() == 16 => {
+                let mut palette = Palette::XTERM;
+                palette.colors.copy_from_slice(colors);
+                Ok(palette)
+            }
+            _ => Err(format!("unknown palette `{}`, expected xterm, vga, tango or 16 colors", s))
+        }
+    }
+}
+
+/// Position of a color in the ansi order, `None` for `Default`
+pub fn ansi_index(color: Color) -> Option<usize> {
+    match color as i32 {
+        c @ 30..=37 => Some((c - 30) as usize),
+        c @ 90..=97 => Some((c - 90 + 8) as usize),
+        _ => None
+    }
+}
+
+/// Number of colors the terminal is able to display
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorDepth {
+    Ansi16 = 1,
+    Ansi256 = 2,
+    TrueColor = 3
+}
+
+// 0 means the depth is detected from the environment
+static DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Force the color depth used to render rgb colors, `None` to detect it
+pub fn set_color_depth(depth: Option<ColorDepth>) {
+    DEPTH.store(depth.map_or(0, |d| d as usize), Ordering::Relaxed)
+}
+
+/// Get the color depth used to render rgb colors
+///
+/// Unless forced with `set_color_depth` it is detected using the
+/// `COLORTERM` environment variable, then the terminfo entry of the
+/// terminal, then the name in `TERM`.
+pub fn color_depth() -> ColorDepth {
+    match DEPTH.load(Ordering::Relaxed) {
+        1 => ColorDepth::Ansi16,
+        2 => ColorDepth::Ansi256,
+        3 => ColorDepth::TrueColor,
+        _ => detect_depth()
+    }
+}
+
+fn detect_depth() -> ColorDepth {
+    match env::var("COLORTERM") {
+        Ok(ref v) if v == "truecolor" || v == "24bit" => return ColorDepth::TrueColor,
+        _ => {}
+    }
+    if let Some(caps) = ::terminfo::capabilities() {
+        return match caps.colors {
+            _ if caps.true_color => ColorDepth::TrueColor,
+            c if c >= 256 => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16
+        };
+    }
+    match env::var("TERM") {
+        Ok(ref v) if v.contains("direct") => ColorDepth::TrueColor,
+        Ok(ref v) if v.contains("256") => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16
+    }
+}
+
+/// Index of the closest color in the xterm 256 colors table
+pub fn to_ansi256(rgb: Rgb) -> u8 {
+    fn cube(v: u8) -> u8 {
+        if v < 48 { 0 } else if v < 115 { 1 } else { (v - 35) / 40 }
+    }
+    let (r, g, b) = (cube(rgb.r), cube(rgb.g), cube(rgb.b));
+    let in_cube = Rgb::new(ansi::cube_level(r), ansi::cube_level(g), ansi::cube_level(b));
+    let avg = (rgb.r as u32 + rgb.g as u32 + rgb.b as u32) / 3;
+    let grey = if avg > 238 { 23 } else { (avg.saturating_sub(3) / 10) as u8 };
+    let v = 8 + grey * 10;
+    if Rgb::new(v, v, v).distance(rgb) < in_cube.distance(rgb) {
+        232 + grey
+    } else {
+        16 + 36 * r + 6 * g + b
+    }
+}
+
+/// Sgr parameters setting an rgb foreground color at the given depth
+pub fn fg_code(rgb: Rgb, depth: ColorDepth) -> String {
+    match depth {
+        ColorDepth::TrueColor => format!("38;2;{};{};{}", rgb.r, rgb.g, rgb.b),
+        ColorDepth::Ansi256 => format!("38;5;{}", to_ansi256(rgb)),
+        ColorDepth::Ansi16 => format!("{}", Palette::XTERM.nearest(rgb) as i32)
+    }
+}
+
+/// Sgr parameters setting an rgb background color at the given depth
+pub fn bg_code(rgb: Rgb, depth: ColorDepth) -> String {
+    match depth {
+        ColorDepth::TrueColor => format!("48;2;{};{};{}", rgb.r, rgb.g, rgb.b),
+        ColorDepth::Ansi256 => format!("48;5;{}", to_ansi256(rgb)),
+        ColorDepth::Ansi16 => {
+            format!("{}", BgColor::from_fg(Palette::XTERM.nearest(rgb)) as i32)
+        }
+    }
+}
+
+/// A color in the OKLab perceptual color space
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Oklab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64
+}
+
+// sRGB transfer functions, between a channel and its linear light
+pub(crate) fn to_linear(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+pub(crate) fn from_linear(c: f64) -> u8 {
+    let c = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl From<Rgb> for Oklab {
+    fn from(c: Rgb) -> Oklab {
+        let (r, g, b) = (to_linear(c.r), to_linear(c.g), to_linear(c.b));
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        Oklab {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s
+        }
+    }
+}
+
+impl From<Oklab> for Rgb {
+    fn from(c: Oklab) -> Rgb {
+        let [r, g, b] = c.linear();
+        Rgb::new(from_linear(r), from_linear(g), from_linear(b))
+    }
+}
+
+impl Oklab {
+    // Linear rgb channels, outside of 0 to 1 when the color is out of gamut
+    fn linear(&self) -> [f64; 3] {
+        let l = self.l + 0.3963377774 * self.a + 0.2158037573 * self.b;
+        let m = self.l - 0.1055613458 * self.a - 0.0638541728 * self.b;
+        let s = self.l - 0.0894841775 * self.a - 1.2914855480 * self.b;
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+        [4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+         -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+         -0.0041960863 * l - 0.7034186147 * m + 1.7076127010 * s]
+    }
+
+    fn in_gamut(&self) -> bool {
+        self.linear().iter().all(|&c| (-1e-5..=1.0 + 1e-5).contains(&c))
+    }
+
+    /// Linear interpolation between two colors, `t` goes from 0 to 1
+    pub fn lerp(&self, other: Oklab, t: f64) -> Oklab {
+        Oklab {
+            l: self.l + (other.l - self.l) * t,
+            a: self.a + (other.a - self.a) * t,
+            b: self.b + (other.b - self.b) * t
+        }
+    }
+}
+
+/// A color in the OKLCH space, OKLab with a chroma and a hue in degrees
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64
+}
+
+impl From<Oklab> for Oklch {
+    fn from(c: Oklab) -> Oklch {
+        Oklch { l: c.l, c: c.a.hypot(c.b), h: c.b.atan2(c.a).to_degrees().rem_euclid(360.0) }
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(c: Oklch) -> Oklab {
+        let h = c.h.to_radians();
+        Oklab { l: c.l, a: c.c * h.cos(), b: c.c * h.sin() }
+    }
+}
+
+impl From<Rgb> for Oklch {
+    fn from(c: Rgb) -> Oklch {
+        Oklch::from(Oklab::from(c))
+    }
+}
+
+impl From<Oklch> for Rgb {
+    fn from(c: Oklch) -> Rgb {
+        Rgb::from(Oklab::from(c))
+    }
+}
+
+/// Mix two colors in OKLab, `t` goes from 0 for `a` to 1 for `b`
+pub fn mix(a: Rgb, b: Rgb, t: f64) -> Rgb {
+    Rgb::from(Oklab::from(a).lerp(Oklab::from(b), t))
+}
+
+// Colors are changed in OKLCH, ansi colors are first resolved with
+// `Rgb::from` or `Palette::resolve`.
+impl Rgb {
+    // Colors out of the sRGB gamut lose chroma until they fit, keeping
+    // their lightness and hue
+    fn map_lch<F: FnOnce(&mut Oklch)>(self, f: F) -> Rgb {
+        let mut lch = Oklch::from(self);
+        f(&mut lch);
+        lch.l = lch.l.clamp(0.0, 1.0);
+        lch.c = lch.c.max(0.0);
+        if !Oklab::from(lch).in_gamut() {
+            let (mut low, mut high) = (0.0, lch.c);
+            for _ in 0..24 {
+                lch.c = (low + high) / 2.0;
+                if Oklab::from(lch).in_gamut() { low = lch.c } else { high = lch.c }
+            }
+            lch.c = low;
+        }
+        Rgb::from(lch)
+    }
+
+    /// Increase the lightness, `amount` goes from 0 to 1
+    pub fn lighten(self, amount: f64) -> Rgb {
+        self.map_lch(|c| c.l += amount)
+    }
+
+    /// Decrease the lightness, `amount` goes from 0 to 1
+    pub fn darken(self, amount: f64) -> Rgb {
+        self.map_lch(|c| c.l -= amount)
+    }
+
+    /// Increase the chroma by a fraction, 0.5 makes it half more colorful
+    pub fn saturate(self, amount: f64) -> Rgb {
+        self.map_lch(|c| c.c *= 1.0 + amount)
+    }
+
+    /// Decrease the chroma by a fraction, 1 makes it grey
+    pub fn desaturate(self, amount: f64) -> Rgb {
+        self.map_lch(|c| c.c *= 1.0 - amount.min(1.0))
+    }
+
+    /// Turn the hue by some degrees
+    pub fn rotate_hue(self, degrees: f64) -> Rgb {
+        self.map_lch(|c| c.h = (c.h + degrees).rem_euclid(360.0))
+    }
+
+    /// The color with the opposite hue
+    pub fn complement(self) -> Rgb {
+        self.rotate_hue(180.0)
+    }
+
+    /// Mix with another color, see `mix`
+    pub fn mix(self, other: Rgb, t: f64) -> Rgb {
+        mix(self, other, t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Oklch, Rgb};
+
+    const COLORS: [Rgb; 4] = [Rgb::new(200, 30, 30), Rgb::new(30, 144, 255), Rgb::new(0, 255, 0), Rgb::new(90, 90, 90)];
+
+    #[test]
+    fn zero_amounts() {
+        for &c in COLORS.iter() {
+            assert_eq!(c.lighten(0.0), c);
+            assert_eq!(c.darken(0.0), c);
+            assert_eq!(c.saturate(0.0), c);
+            assert_eq!(c.desaturate(0.0), c);
+            assert_eq!(c.rotate_hue(0.0), c);
+        }
+    }
+
+    #[test]
+    fn full_lightness() {
+        for &c in COLORS.iter() {
+            assert_eq!(c.lighten(1.0), Rgb::new(255, 255, 255));
+            assert_eq!(c.darken(1.0), Rgb::new(0, 0, 0));
+        }
+    }
+
+    #[test]
+    fn hue_turns() {
+        for &c in COLORS.iter() {
+            assert_eq!(c.rotate_hue(360.0), c);
+            assert_eq!(c.rotate_hue(-360.0), c);
+        }
+    }
+
+    #[test]
+    fn gamut() {
+        // lightening a saturated color keeps its hue instead of clipping a channel
+        let red = Rgb::new(255, 0, 0);
+        let light = red.lighten(0.2);
+        let (before, after) = (Oklch::from(red), Oklch::from(light));
+        assert!((before.h - after.h).abs() < 2.0);
+        assert!(after.l > before.l + 0.15);
+        let saturated = Rgb::new(120, 100, 100).saturate(10.0);
+        assert!((Oklch::from(saturated).h - Oklch::from(Rgb::new(120, 100, 100)).h).abs() < 2.0);
+    }
+
+    #[test]
+    fn x11_colors() {
+        assert_eq!(Rgb::from_x11("rgb:f/8/0"), Some(Rgb::new(255, 136, 0)));
+        assert_eq!(Rgb::from_x11("rgb:ff/80/00"), Some(Rgb::new(255, 128, 0)));
+        assert_eq!(Rgb::from_x11("rgb:fff/800/000"), Some(Rgb::new(255, 128, 0)));
+        assert_eq!(Rgb::from_x11("rgb:ffff/8080/0000"), Some(Rgb::new(255, 128, 0)));
+        assert_eq!(Rgb::from_x11("rgba:ffff/0000/0000/ffff"), Some(Rgb::new(255, 0, 0)));
+        assert_eq!(Rgb::from_x11("#1e90ff"), Some(Rgb::new(30, 144, 255)));
+        assert_eq!(Rgb::from_x11("rgb:fffff/0/0"), None);
+        assert_eq!(Rgb::from_x11("rgb:ff/00"), None);
+        assert_eq!(Rgb::from_x11("rgb:gg/00/00"), None);
+        assert_eq!(Rgb::from_x11("#fff"), None);
+    }
+}
@@ -0,0 +1,204 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Activity indicator animated on a background thread.
+//
+// ```Rust
+// let spinner = Spinner::start(Frames::Dots, "Loading".blue());
+// do_work();
+// spinner.success("Loaded");
+// ```
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use Color;
+use internal;
+use term::{self, Stream};
+
+/// Named sets of animation frames
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Frames {
+    Dots,
+    Line,
+    Braille,
+    Arc
+}
+
+impl Frames {
+    /// The frames of the animation
+    pub fn frames(&self) -> &'static [&'static str] {
+        match *self {
+            Frames::Dots => &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+            Frames::Line => &["-", "\\", "|", "/"],
+            Frames::Braille => &["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"],
+            Frames::Arc => &["◜", "◠", "◝", "◞", "◡", "◟"]
+        }
+    }
+
+    /// Delay between two frames
+    pub fn interval(&self) -> Duration {
+        match *self {
+            Frames::Line => Duration::from_millis(130),
+            Frames::Arc => Duration::from_millis(100),
+            _ => Duration::from_millis(80)
+        }
+    }
+}
+
+struct Shared {
+    message: Mutex<String>,
+    stop: AtomicBool
+}
+
+/// A spinner running until it is finished or dropped
+pub struct Spinner {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+    tty: bool
+}
+
+impl Spinner {
+    /// Start a spinner on stderr with a message displayed after it
+    pub fn start(frames: Frames, message: String) -> Spinner {
+        Spinner::with_color(frames, Color::Cyan, message)
+    }
+
+    /// Start a spinner whose frames are drawn in `color`
+    pub fn with_color(frames: Frames, color: Color, message: String) -> Spinner {
+        let shared = Arc::new(Shared {
+            message: Mutex::new(message),
+            stop: AtomicBool::new(false)
+        });
+        let tty = io::stderr().is_terminal();
+        let thread = if tty {
+            let shared = shared.clone();
+            // colored here, so the global colors of the caller thread are used
+            let colored = frames.frames().iter()
+                .map(|f| internal::pack(color, String::from(*f)))
+                .collect();
+            let interval = frames.interval();
+            Some(thread::spawn(move || animate(colored, interval, &shared)))
+        } else {
+            None
+        };
+        Spinner { shared, thread, tty }
+    }
+
+    /// Replace the message displayed after the spinner
+    pub fn set_message(&self, message: String) {
+        *lock(&self.shared.message) = message;
+    }
+
+    /// Stop the spinner and print a green check mark followed by `message`
+    pub fn success(mut self, message: &str) {
+        self.finish(symbol("✔", Color::Green, term::colors_enabled(Stream::Stderr)), message)
+    }
+
+    /// Stop the spinner and print a red cross followed by `message`
+    pub fn failure(mut self, message: &str) {
+        self.finish(symbol("✖", Color::Red, term::colors_enabled(Stream::Stderr)), message)
+    }
+
+    /// Stop the spinner and erase it
+    pub fn stop(mut self) {
+        self.halt();
+    }
+
+    fn finish(&mut self, symbol: String, message: &str) {
+        self.halt();
+        let _ = writeln!(io::stderr(), "{} {}", symbol, message);
+    }
+
+    // Stop the animation thread, erase the line and show the cursor again
+    fn halt(&mut self) {
+        if self.shared.stop.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if self.tty {
+            let mut err = io::stderr();
+            let _ = write!(err, "\r\x1b[2K\x1b[?25h");
+            let _ = err.flush();
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.halt()
+    }
+}
+
+// The symbol written when the spinner is finished
+fn symbol(symbol: &str, color: Color, colored: bool) -> String {
+    if colored { internal::pack(color, String::from(symbol)) } else { String::from(symbol) }
+}
+
+// A panic while the message is locked must not prevent the cleanup
+fn lock(message: &Mutex<String>) -> ::std::sync::MutexGuard<'_, String> {
+    message.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn animate(frames: Vec<String>, interval: Duration, shared: &Shared) {
+    let mut err = io::stderr();
+    let _ = write!(err, "\x1b[?25l");
+    for frame in frames.iter().cycle() {
+        if shared.stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let _ = write!(err, "\r\x1b[2K{} {}", frame, *lock(&shared.message));
+        let _ = err.flush();
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{symbol, Frames};
+    use ansi;
+    use Color;
+
+    #[test]
+    fn frames() {
+        for &frames in &[Frames::Dots, Frames::Line, Frames::Braille, Frames::Arc] {
+            assert!(!frames.frames().is_empty());
+            assert!(frames.frames().iter().all(|f| ansi::visible_width(f) == 1));
+            assert!(frames.interval().as_millis() > 0);
+        }
+    }
+
+    #[test]
+    fn uncolored_symbol() {
+        assert_eq!(symbol("✔", Color::Green, false), "✔");
+        assert_eq!(symbol("✖", Color::Red, false), "✖");
+    }
+
+    #[test]
+    fn colored_symbol() {
+        assert_eq!(ansi::strip(&symbol("✔", Color::Green, true)), "✔");
+    }
+}
@@ -0,0 +1,177 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Colored texts honoring the width, alignment and precision format specs.
+//
+// ```Rust
+// // pads "ok" to 10 visible columns, not to its length in bytes
+// println!("[{:>10}]", "ok".green().styled());
+// ```
+
+use std::fmt;
+
+use unicode_width::UnicodeWidthChar;
+
+use ansi::{self, Token};
+use wrap;
+
+/// Where the padding added by a format width goes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Padding {
+    /// Around the colored text, the fill is not colored
+    Outside,
+    /// Inside the colored text, the fill takes its colors
+    Inside
+}
+
+/// A colored text whose formatting applies to its visible part
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Styled {
+    text: String,
+    padding: Padding
+}
+
+impl Styled {
+    /// Wrap a colored text, padding goes outside of the colors
+    pub fn new(text: String) -> Styled {
+        Styled { text, padding: Padding::Outside }
+    }
+
+    /// Set where the padding goes
+    pub fn padding(mut self, padding: Padding) -> Styled {
+        self.padding = padding;
+        self
+    }
+
+    /// Get the colored text
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Get back the colored text
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+impl From<String> for Styled {
+    fn from(text: String) -> Styled {
+        Styled::new(text)
+    }
+}
+
+impl From<Styled> for String {
+    fn from(styled: Styled) -> String {
+        styled.text
+    }
+}
+
+// Split a text between its leading escapes, its body and its trailing escapes
+fn split(text: &str) -> (&str, &str, &str) {
+    let mut start = 0;
+    let mut end = 0;
+    let mut pos = 0;
+    let mut body = false;
+    for token in ansi::tokens(text) {
+        let len = match token { Token::Escape(s) | Token::Text(s) => s.len() };
+        match token {
+            Token::Escape(_) if !body => start = pos + len,
+            Token::Text(_) => {
+                body = true;
+                end = pos + len;
+            }
+            _ => {}
+        }
+        pos += len;
+    }
+    let end = end.max(start);
+    (&text[..start], &text[start..end], &text[end..])
+}
+
+impl fmt::Display for Styled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match f.precision() {
+            Some(p) => wrap::truncate(&self.text, p, ""),
+            None => self.text.clone()
+        };
+        let missing = f.width().unwrap_or(0).saturating_sub(ansi::visible_width(&text));
+        if missing == 0 {
+            return f.write_str(&text);
+        }
+        let (left, right) = match f.align() {
+            Some(fmt::Alignment::Right) => (missing, 0),
+            Some(fmt::Alignment::Center) => (missing / 2, missing - missing / 2),
+            Some(fmt::Alignment::Left) | None => (0, missing)
+        };
+        // a wide fill character takes several columns, spaces complete it
+        let fill = f.fill();
+        let fill_width = fill.width().unwrap_or(1).max(1);
+        let fill = |n: usize| fill.to_string().repeat(n / fill_width) + &" ".repeat(n % fill_width);
+        let (left, right) = (fill(left), fill(right));
+        match self.padding {
+            Padding::Outside => write!(f, "{}{}{}", left, text, right),
+            Padding::Inside => {
+                let (prefix, body, suffix) = split(&text);
+                write!(f, "{}{}{}{}{}", prefix, left, body, right, suffix)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Padding, Styled};
+
+    fn green(text: &str) -> Styled {
+        Styled::new(format!("\x1b[32m{}\x1b[0m", text))
+    }
+
+    #[test]
+    fn align() {
+        assert_eq!(format!("[{:>5}]", green("ok")), "[   \x1b[32mok\x1b[0m]");
+        assert_eq!(format!("[{:<5}]", green("ok")), "[\x1b[32mok\x1b[0m   ]");
+        assert_eq!(format!("[{:^5}]", green("ok")), "[ \x1b[32mok\x1b[0m  ]");
+        assert_eq!(format!("[{:5}]", green("ok")), "[\x1b[32mok\x1b[0m   ]");
+        assert_eq!(format!("[{:1}]", green("ok")), "[\x1b[32mok\x1b[0m]");
+    }
+
+    #[test]
+    fn inside() {
+        let ok = green("ok").padding(Padding::Inside);
+        assert_eq!(format!("[{:>4}]", ok), "[\x1b[32m  ok\x1b[0m]");
+    }
+
+    #[test]
+    fn precision() {
+        assert_eq!(format!("[{:4.1}]", green("ok")), "[\x1b[32mo\x1b[0;39;49m   ]");
+    }
+
+    #[test]
+    fn fill() {
+        assert_eq!(format!("[{:*>4}]", green("ok")), "[**\x1b[32mok\x1b[0m]");
+        assert_eq!(format!("[{:日>5}]", green("ok")), "[日 \x1b[32mok\x1b[0m]");
+    }
+
+    #[test]
+    fn wide_text() {
+        assert_eq!(format!("[{:>6}]", green("日本")), "[  \x1b[32m日本\x1b[0m]");
+    }
+}
@@ -0,0 +1,364 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Tables of colored cells, aligned on their visible width.
+//
+// ```Rust
+// let mut table = Table::new()
+//     .header(vec!["Name", "Status"])
+//     .header_style(Bold)
+//     .border(Border::Rounded)
+//     .zebra(Blackb);
+// table.add_row(vec!["db".to_string(), "up".green()]);
+// table.add_row(vec!["cache".to_string(), "down".red()]);
+// println!("{}", table);
+// ```
+
+use std::fmt;
+
+use {BgColor, Style};
+use ansi::{self, Token};
+use internal;
+use wrap;
+
+/// Horizontal alignment of the content of a column
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+    Right
+}
+
+/// Lines drawn around and between the cells
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Border {
+    None,
+    Ascii,
+    Single,
+    Rounded,
+    Double,
+    Heavy
+}
+
+struct BorderChars {
+    h: char,
+    v: char,
+    // top, middle and bottom lines: left end, junction, right end
+    top: [char; 3],
+    mid: [char; 3],
+    bottom: [char; 3]
+}
+
+impl Border {
+    fn chars(&self) -> Option<BorderChars> {
+        let c = |h, v, top, mid, bottom| Some(BorderChars { h, v, top, mid, bottom });
+        match *self {
+            Border::None => None,
+            Border::Ascii => c('-', '|', ['+'; 3], ['+'; 3], ['+'; 3]),
+            Border::Single => c('─', '│', ['┌', '┬', '┐'], ['├', '┼', '┤'], ['└', '┴', '┘']),
+            Border::Rounded => c('─', '│', ['╭', '┬', '╮'], ['├', '┼', '┤'], ['╰', '┴', '╯']),
+            Border::Double => c('═', '║', ['╔', '╦', '╗'], ['╠', '╬', '╣'], ['╚', '╩', '╝']),
+            Border::Heavy => c('━', '┃', ['┏', '┳', '┓'], ['┣', '╋', '┫'], ['┗', '┻', '┛'])
+        }
+    }
+}
+
+/// A table builder
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+    aligns: Vec<Align>,
+    max_widths: Vec<Option<usize>>,
+    border: Border,
+    header_style: Option<Style>,
+    zebra: Option<BgColor>
+}
+
+impl Default for Table {
+    fn default() -> Table {
+        Table::new()
+    }
+}
+
+impl Table {
+    /// Create an empty table without borders
+    pub fn new() -> Table {
+        Table {
+            header: Vec::new(),
+            rows: Vec::new(),
+            aligns: Vec::new(),
+            max_widths: Vec::new(),
+            border: Border::None,
+            header_style: None,
+            zebra: None
+        }
+    }
+
+    /// Set the header row
+    pub fn header<S: Into<String>>(mut self, cells: Vec<S>) -> Table {
+        self.header = cells.into_iter().map(|c| c.into()).collect();
+        self
+    }
+
+    /// Set the style of the header cells
+    pub fn header_style(mut self, style: Style) -> Table {
+        self.header_style = Some(style);
+        self
+    }
+
+    /// Set the alignment of a column, columns are left aligned by default
+    pub fn align(mut self, column: usize, align: Align) -> Table {
+        if self.aligns.len() <= column {
+            self.aligns.resize(column + 1, Align::Left);
+        }
+        self.aligns[column] = align;
+        self
+    }
+
+    /// Wrap the cells of a column wider than `width`
+    pub fn max_width(mut self, column: usize, width: usize) -> Table {
+        if self.max_widths.len() <= column {
+            self.max_widths.resize(column + 1, None);
+        }
+        self.max_widths[column] = Some(width.max(1));
+        self
+    }
+
+    /// Set the border style
+    pub fn border(mut self, border: Border) -> Table {
+        self.border = border;
+        self
+    }
+
+    /// Paint every other row with a background color
+    pub fn zebra(mut self, color: BgColor) -> Table {
+        self.zebra = Some(color);
+        self
+    }
+
+    /// Add a row of cells
+    pub fn add_row<S: Into<String>>(&mut self, cells: Vec<S>) {
+        self.rows.push(cells.into_iter().map(|c| c.into()).collect());
+    }
+
+    // Lines of each cell of a row, wrapped to the max width of their column
+    fn layout(&self, row: &[String], columns: usize) -> Vec<Vec<String>> {
+        (0..columns).map(|i| {
+            let cell = row.get(i).map_or("", |c| c.as_str());
+            let width = self.max_widths.get(i).cloned().unwrap_or(None);
+            wrap::wrap(cell, width.unwrap_or(usize::MAX))
+        }).collect()
+    }
+
+    /// Render the table
+    pub fn render(&self) -> String {
+        let columns = self.rows.iter().map(|r| r.len())
+            .chain(Some(self.header.len())).max().unwrap_or(0);
+        let mut header = self.layout(&self.header, columns);
+        if let Some(style) = self.header_style {
+            for lines in header.iter_mut() {
+                for line in lines.iter_mut() {
+                    *line = internal::pack(style, line.clone());
+                }
+            }
+        }
+        let rows: Vec<_> = self.rows.iter().map(|r| self.layout(r, columns)).collect();
+
+        let mut widths = vec![0; columns];
+        for row in rows.iter().chain(Some(&header)) {
+            for (i, lines) in row.iter().enumerate() {
+                for line in lines {
+                    widths[i] = widths[i].max(ansi::visible_width(line));
+                }
+            }
+        }
+
+        let border = self.border.chars();
+        let mut out = String::new();
+        if let Some(ref b) = border {
+            out.push_str(&rule(&widths, b.h, b.top));
+        }
+        if !self.header.is_empty() {
+            out.push_str(&self.row_lines(&header, &widths, None));
+            match border {
+                Some(ref b) => out.push_str(&rule(&widths, b.h, b.mid)),
+                None => {
+                    let lines: Vec<_> = widths.iter().map(|&w| "─".repeat(w)).collect();
+                    out.push_str(&lines.join("  "));
+                    out.push('\n');
+                }
+            }
+        }
+        for (i, row) in rows.iter().enumerate() {
+            let bg = if i % 2 == 1 { self.zebra } else { None };
+            out.push_str(&self.row_lines(row, &widths, bg));
+        }
+        if let Some(ref b) = border {
+            out.push_str(&rule(&widths, b.h, b.bottom));
+        }
+        out
+    }
+
+    fn row_lines(&self, row: &[Vec<String>], widths: &[usize], bg: Option<BgColor>) -> String {
+        let height = row.iter().map(|l| l.len()).max().unwrap_or(0).max(1);
+        let border = self.border.chars();
+        let mut out = String::new();
+        for n in 0..height {
+            let cells: Vec<String> = row.iter().enumerate().map(|(i, lines)| {
+                let line = lines.get(n).map_or("", |l| l.as_str());
+                let align = self.aligns.get(i).cloned().unwrap_or(Align::Left);
+                match border {
+                    Some(_) => format!(" {} ", pad(line, widths[i], align)),
+                    None => pad(line, widths[i], align)
+                }
+            }).collect();
+            match (border.as_ref(), bg) {
+                (Some(b), bg) => {
+                    let v = b.v.to_string();
+                    let cells: Vec<String> = match bg {
+                        Some(bg) => cells.iter().map(|c| with_background(c, bg)).collect(),
+                        None => cells
+                    };
+                    out.push_str(&format!("{}{}{}", v, cells.join(&v), v));
+                }
+                // the gaps between the cells are painted too
+                (None, Some(bg)) => out.push_str(&with_background(&cells.join("  "), bg)),
+                (None, None) => out.push_str(&cells.join("  "))
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+fn rule(widths: &[usize], h: char, ends: [char; 3]) -> String {
+    let parts: Vec<String> = widths.iter()
+        .map(|&w| h.to_string().repeat(w + 2))
+        .collect();
+    format!("{}{}{}\n", ends[0], parts.join(&ends[1].to_string()), ends[2])
+}
+
+/// Pad a text containing escapes to `width` visible columns
+pub fn pad(text: &str, width: usize, align: Align) -> String {
+    let missing = width.saturating_sub(ansi::visible_width(text));
+    let (left, right) = match align {
+        Align::Left => (0, missing),
+        Align::Right => (missing, 0),
+        Align::Center => (missing / 2, missing - missing / 2)
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+// Paint a text with a background color kept across the escapes it contains
+fn with_background(text: &str, bg: BgColor) -> String {
+    let set = match internal::code(bg) {
+        Some(code) => format!("\x1b[{}m", code),
+        None => return String::from(text)
+    };
+    let mut out = set.clone();
+    for token in ansi::tokens(text) {
+        match token {
+            Token::Escape(s) => {
+                out.push_str(s);
+                out.push_str(&set);
+            }
+            Token::Text(s) => out.push_str(s)
+        }
+    }
+    let (fg, bg) = internal::get_glob();
+    out.push_str(&format!("\x1b[0;{};{}m", fg, bg));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Align, Border, Table};
+    use ansi;
+    use internal;
+    use BgColor;
+
+    fn table() -> Table {
+        let mut table = Table::new().header(vec!["name", "size"]).align(1, Align::Right);
+        table.add_row(vec!["a", "1"]);
+        table.add_row(vec!["bcd", "234567"]);
+        table
+    }
+
+    #[test]
+    fn borderless() {
+        assert_eq!(table().render(), "\
+name    size
+────  ──────
+a          1
+bcd   234567
+");
+    }
+
+    #[test]
+    fn borders() {
+        assert_eq!(table().border(Border::Ascii).render(), "\
++------+--------+
+| name |   size |
++------+--------+
+| a    |      1 |
+| bcd  | 234567 |
++------+--------+
+");
+    }
+
+    #[test]
+    fn wide_cells() {
+        let mut table = Table::new().border(Border::Single);
+        table.add_row(vec!["日本", "x"]);
+        table.add_row(vec!["a", "y"]);
+        assert_eq!(table.render(), "\
+┌──────┬───┐
+│ 日本 │ x │
+│ a    │ y │
+└──────┴───┘
+");
+    }
+
+    #[test]
+    fn wrapped_cells() {
+        let mut table = Table::new().max_width(0, 5);
+        table.add_row(vec!["the quick fox", "z"]);
+        assert_eq!(table.render(), "the    z\nquick   \nfox     \n");
+    }
+
+    #[test]
+    fn zebra_gaps() {
+        let mut table = Table::new().zebra(BgColor::Blackb);
+        table.add_row(vec!["a", "b"]);
+        table.add_row(vec!["c", "d"]);
+        let out = table.render();
+        assert_eq!(ansi::strip(&out), "a  b\nc  d\n");
+        if let Some(code) = internal::code(BgColor::Blackb) {
+            assert!(out.ends_with(&format!("\x1b[{}mc  d\x1b[0;39;49m\n", code)));
+        }
+    }
+}
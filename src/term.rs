@@ -0,0 +1,84 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Detection of whether colors should be written to an output.
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A standard output stream
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stream {
+    Stdout,
+    Stderr
+}
+
+impl Stream {
+    /// Is the stream attached to a terminal
+    pub fn is_terminal(&self) -> bool {
+        match *self {
+            Stream::Stdout => io::stdout().is_terminal(),
+            Stream::Stderr => io::stderr().is_terminal()
+        }
+    }
+}
+
+// 0 means detected, 1 disabled and 2 enabled
+static ENABLED: AtomicUsize = AtomicUsize::new(0);
+
+/// Force colors on or off, `None` to detect it
+pub fn set_colors_enabled(enabled: Option<bool>) {
+    ENABLED.store(enabled.map_or(0, |e| if e { 2 } else { 1 }), Ordering::Relaxed)
+}
+
+/// Should colors be written to a stream
+///
+/// Unless forced with `set_colors_enabled`, colors are disabled by a
+/// non empty `NO_COLOR` and enabled by a `CLICOLOR_FORCE` other than 0.
+/// Otherwise they are written to terminals other than `TERM=dumb`.
+pub fn colors_enabled(stream: Stream) -> bool {
+    colors_enabled_for(stream.is_terminal())
+}
+
+/// Should colors be written to a writer, knowing if it is a terminal
+pub fn colors_enabled_for(is_terminal: bool) -> bool {
+    match ENABLED.load(Ordering::Relaxed) {
+        1 => false,
+        2 => true,
+        _ => env_enabled().unwrap_or(is_terminal)
+    }
+}
+
+fn env_enabled() -> Option<bool> {
+    match env::var("NO_COLOR") {
+        Ok(ref v) if !v.is_empty() => return Some(false),
+        _ => {}
+    }
+    match env::var("CLICOLOR_FORCE") {
+        Ok(ref v) if !v.is_empty() && v != "0" => return Some(true),
+        _ => {}
+    }
+    match env::var("TERM") {
+        Ok(ref v) if v == "dumb" => Some(false),
+        _ => None
+    }
+}
@@ -0,0 +1,477 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Compiled terminfo entries, and what they allow the colors and styles to use.
+//
+// ```Rust
+// let info = Terminfo::load("xterm-256color")?;
+// assert_eq!(info.colors(), Some(256));
+// ```
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use rgb::ColorDepth;
+
+// Numbers are 16 bits in the legacy format and 32 bits in the extended one
+const MAGIC_LEGACY: u16 = 0o432;
+const MAGIC_EXTENDED: u16 = 0o1036;
+
+// Positions of the standard capabilities used here, in the ncurses order
+const NUMBERS: [(&str, usize); 5] = [("cols", 0), ("it", 1), ("lines", 2), ("colors", 13), ("pairs", 14)];
+const STRINGS: [(&str, usize); 11] = [
+    ("blink", 26), ("bold", 27), ("dim", 30), ("invis", 32), ("rev", 34), ("smso", 35),
+    ("smul", 36), ("sgr0", 39), ("sitm", 311), ("setaf", 359), ("setab", 360)
+];
+
+/// A compiled terminfo entry
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Terminfo {
+    names: Vec<String>,
+    booleans: Vec<bool>,
+    numbers: Vec<Option<i32>>,
+    strings: Vec<Option<Vec<u8>>>,
+    ext_booleans: HashMap<String, bool>,
+    ext_numbers: HashMap<String, i32>,
+    ext_strings: HashMap<String, Vec<u8>>
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid terminfo entry: {}", msg))
+}
+
+// A cursor over the little endian fields of an entry
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len())
+            .ok_or_else(|| invalid("truncated"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn i16(&mut self) -> io::Result<i16> {
+        let b = self.bytes(2)?;
+        Ok(i16::from_le_bytes([b[0], b[1]]))
+    }
+
+    // A count, negative counts are invalid
+    fn count(&mut self) -> io::Result<usize> {
+        let n = self.i16()?;
+        if n < 0 { Err(invalid("negative count")) } else { Ok(n as usize) }
+    }
+
+    // A number of the given width, `None` when absent or cancelled
+    fn number(&mut self, wide: bool) -> io::Result<Option<i32>> {
+        let n = if wide {
+            let b = self.bytes(4)?;
+            i32::from_le_bytes([b[0], b[1], b[2], b[3]])
+        } else {
+            self.i16()? as i32
+        };
+        Ok(if n < 0 { None } else { Some(n) })
+    }
+
+    fn align(&mut self) {
+        if self.pos % 2 == 1 && self.pos < self.data.len() {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+}
+
+// The nul terminated string at an offset of a table
+fn table_str(table: &[u8], offset: i16) -> Option<&[u8]> {
+    if offset < 0 {
+        return None;
+    }
+    let rest = table.get(offset as usize..)?;
+    Some(&rest[..rest.iter().position(|&b| b == 0)?])
+}
+
+impl Terminfo {
+    /// Parse a compiled entry, in the legacy or the extended number format
+    pub fn parse(data: &[u8]) -> io::Result<Terminfo> {
+        let mut r = Reader { data, pos: 0 };
+        let wide = match r.i16()? as u16 {
+            MAGIC_LEGACY => false,
+            MAGIC_EXTENDED => true,
+            _ => return Err(invalid("bad magic number"))
+        };
+        let (names_len, bool_count, num_count) = (r.count()?, r.count()?, r.count()?);
+        let (str_count, table_len) = (r.count()?, r.count()?);
+
+        let names = r.bytes(names_len)?;
+        let names = names.split(|&b| b == 0).next().unwrap_or(&[]);
+        let mut info = Terminfo {
+            names: String::from_utf8_lossy(names).split('|').map(String::from).collect(),
+            ..Terminfo::default()
+        };
+        info.booleans = r.bytes(bool_count)?.iter().map(|&b| b == 1).collect();
+        r.align();
+        for _ in 0..num_count {
+            info.numbers.push(r.number(wide)?);
+        }
+        let offsets = (0..str_count).map(|_| r.i16()).collect::<io::Result<Vec<i16>>>()?;
+        let table = r.bytes(table_len)?;
+        info.strings = offsets.iter().map(|&o| table_str(table, o).map(|s| s.to_vec())).collect();
+
+        r.align();
+        if !r.at_end() {
+            info.parse_extended(&mut r, wide)?;
+        }
+        Ok(info)
+    }
+
+    // The user defined capabilities, after the standard ones
+    fn parse_extended(&mut self, r: &mut Reader, wide: bool) -> io::Result<()> {
+        let (bool_count, num_count, str_count) = (r.count()?, r.count()?, r.count()?);
+        let (_, table_len) = (r.count()?, r.count()?);
+        let booleans: Vec<bool> = r.bytes(bool_count)?.iter().map(|&b| b == 1).collect();
+        r.align();
+        let numbers = (0..num_count).map(|_| r.number(wide)).collect::<io::Result<Vec<_>>>()?;
+        let offsets = (0..str_count).map(|_| r.i16()).collect::<io::Result<Vec<i16>>>()?;
+        let name_offsets = (0..bool_count + num_count + str_count).map(|_| r.i16())
+            .collect::<io::Result<Vec<i16>>>()?;
+        let table = r.bytes(table_len)?;
+
+        // the names follow the last string value
+        let values: Vec<Option<&[u8]>> = offsets.iter().map(|&o| table_str(table, o)).collect();
+        let names_start = offsets.iter().zip(&values)
+            .filter_map(|(&o, v)| v.map(|v| o as usize + v.len() + 1))
+            .max().unwrap_or(0);
+        let names_table = table.get(names_start..).unwrap_or(&[]);
+        let names = name_offsets.iter().map(|&o| {
+            table_str(names_table, o).map(|n| String::from_utf8_lossy(n).into_owned())
+                .ok_or_else(|| invalid("missing capability name"))
+        }).collect::<io::Result<Vec<String>>>()?;
+
+        // one name for each value, booleans first
+        let mut names = names.into_iter();
+        for value in booleans {
+            self.ext_booleans.insert(names.next().unwrap_or_default(), value);
+        }
+        for value in numbers {
+            let name = names.next().unwrap_or_default();
+            if let Some(value) = value {
+                self.ext_numbers.insert(name, value);
+            }
+        }
+        for value in values {
+            let name = names.next().unwrap_or_default();
+            if let Some(value) = value {
+                self.ext_strings.insert(name, value.to_vec());
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the entry of a terminal from the terminfo directories
+    ///
+    /// They are `$TERMINFO`, `~/.terminfo`, the ones of `$TERMINFO_DIRS`,
+    /// then `/etc/terminfo`, `/lib/terminfo` and `/usr/share/terminfo`.
+    pub fn load(term: &str) -> io::Result<Terminfo> {
+        if term.is_empty() || term.contains('/') || term.starts_with('.') {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid terminal name {:?}", term)));
+        }
+        let first = term.chars().next().unwrap_or('x');
+        for dir in search_dirs() {
+            // `x/xterm` on most systems, `78/xterm` on macOS
+            for sub in &[first.to_string(), format!("{:x}", first as u32)] {
+                if let Ok(data) = fs::read(dir.join(sub).join(term)) {
+                    return Terminfo::parse(&data);
+                }
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::NotFound, format!("no terminfo entry for {}", term)))
+    }
+
+    /// Read the entry of the terminal named by `TERM`
+    pub fn from_env() -> io::Result<Terminfo> {
+        match env::var("TERM") {
+            Ok(term) => Terminfo::load(&term),
+            Err(_) => Err(io::Error::new(io::ErrorKind::NotFound, "TERM is not set"))
+        }
+    }
+
+    /// The names of the terminal, the last one is its description
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// A boolean capability, only the user defined ones are known by name
+    pub fn flag(&self, name: &str) -> bool {
+        self.ext_booleans.get(name).cloned().unwrap_or(false)
+    }
+
+    /// A numeric capability
+    pub fn number(&self, name: &str) -> Option<i32> {
+        match NUMBERS.iter().find(|n| n.0 == name) {
+            Some(&(_, i)) => self.numbers.get(i).cloned().unwrap_or(None),
+            None => self.ext_numbers.get(name).cloned()
+        }
+    }
+
+    /// A string capability, not expanded
+    pub fn string(&self, name: &str) -> Option<&[u8]> {
+        match STRINGS.iter().find(|s| s.0 == name) {
+            Some(&(_, i)) => self.strings.get(i).and_then(|s| s.as_deref()),
+            None => self.ext_strings.get(name).map(|s| &s[..])
+        }
+    }
+
+    /// Number of colors, `colors`
+    pub fn colors(&self) -> Option<i32> {
+        self.number("colors")
+    }
+
+    /// Set the foreground color, `setaf`
+    pub fn setaf(&self) -> Option<&[u8]> {
+        self.string("setaf")
+    }
+
+    /// Set the background color, `setab`
+    pub fn setab(&self) -> Option<&[u8]> {
+        self.string("setab")
+    }
+
+    /// Enter italic mode, `sitm`
+    pub fn sitm(&self) -> Option<&[u8]> {
+        self.string("sitm")
+    }
+
+    /// Enter underline mode, `smul`
+    pub fn smul(&self) -> Option<&[u8]> {
+        self.string("smul")
+    }
+
+    /// Set an underline style such as curly, `Smulx`
+    pub fn smulx(&self) -> Option<&[u8]> {
+        self.string("Smulx")
+    }
+
+    /// Are 24 bits colors supported, with `Tc` or `RGB`
+    ///
+    /// `RGB` may be a boolean, a number or a string.
+    pub fn true_color(&self) -> bool {
+        self.flag("Tc") || self.flag("RGB")
+            || self.ext_numbers.contains_key("RGB") || self.ext_strings.contains_key("RGB")
+    }
+
+    /// The depth rgb colors can use
+    pub fn color_depth(&self) -> ColorDepth {
+        match self.colors() {
+            _ if self.true_color() => ColorDepth::TrueColor,
+            Some(c) if c >= 0x100_0000 => ColorDepth::TrueColor,
+            Some(c) if c >= 256 => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16
+        }
+    }
+}
+
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+    if let Ok(list) = env::var("TERMINFO_DIRS") {
+        // an empty element stands for the system directory
+        dirs.extend(list.split(':').map(|d| PathBuf::from(if d.is_empty() { "/usr/share/terminfo" } else { d })));
+    }
+    for dir in &["/etc/terminfo", "/lib/terminfo", "/usr/share/terminfo"] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs
+}
+
+/// What a terminal displays, deciding which colors and styles are written
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Capabilities {
+    /// Number of colors, 0 without colors
+    pub colors: u32,
+    pub true_color: bool,
+    pub bold: bool,
+    pub faint: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub blink: bool,
+    pub reverse: bool,
+    pub concealed: bool
+}
+
+impl Capabilities {
+    /// Everything, used when the terminal is unknown
+    pub const ALL: Capabilities = Capabilities {
+        colors: 256, true_color: true, bold: true, faint: true, italic: true,
+        underline: true, blink: true, reverse: true, concealed: true
+    };
+
+    /// The capabilities of a terminfo entry
+    pub fn of(info: &Terminfo) -> Capabilities {
+        let has = |name| info.string(name).is_some();
+        Capabilities {
+            colors: if has("setaf") { info.colors().unwrap_or(0).max(0) as u32 } else { 0 },
+            true_color: info.color_depth() == ColorDepth::TrueColor,
+            bold: has("bold"),
+            faint: has("dim"),
+            italic: has("sitm"),
+            underline: has("smul"),
+            blink: has("blink"),
+            reverse: has("rev"),
+            concealed: has("invis")
+        }
+    }
+
+    /// The sgr code to write for a color or style code, `None` to skip it
+    ///
+    /// Colors are skipped on terminals without colors. Bright colors are
+    /// kept on 8 colors terminals, which mostly display them anyway.
+    pub fn filter(&self, code: i32) -> Option<i32> {
+        let allowed = match code {
+            1 => self.bold,
+            2 => self.faint,
+            3 => self.italic,
+            4 => self.underline,
+            5 => self.blink,
+            7 => self.reverse,
+            8 => self.concealed,
+            30..=37 | 40..=47 | 90..=97 | 100..=107 => self.colors >= 8,
+            _ => true
+        };
+        if allowed { Some(code) } else { None }
+    }
+}
+
+static DETECTED: OnceLock<Option<Capabilities>> = OnceLock::new();
+static FORCED: RwLock<Option<Capabilities>> = RwLock::new(None);
+
+/// Force the capabilities of the terminal, `None` to read them from terminfo
+pub fn set_capabilities(caps: Option<Capabilities>) {
+    *FORCED.write().unwrap_or_else(|e| e.into_inner()) = caps;
+}
+
+/// The capabilities of the terminal, `None` when it has no terminfo entry
+///
+/// Unless forced with `set_capabilities`, the entry named by `TERM` is read
+/// once.
+pub fn capabilities() -> Option<Capabilities> {
+    let forced = *FORCED.read().unwrap_or_else(|e| e.into_inner());
+    forced.or_else(|| *DETECTED.get_or_init(|| Terminfo::from_env().ok().map(|info| Capabilities::of(&info))))
+}
+
+// The sgr code written for a color or style code, `None` to skip it
+pub fn filter(code: i32) -> Option<i32> {
+    capabilities().unwrap_or(Capabilities::ALL).filter(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Capabilities, Terminfo};
+    use rgb::ColorDepth;
+
+    const EIGHT: &[u8] = include_bytes!("../examples/terminfo/c/colorize-8");
+    const DIRECT: &[u8] = include_bytes!("../examples/terminfo/c/colorize-direct");
+    const MONO: &[u8] = include_bytes!("../examples/terminfo/c/colorize-mono");
+
+    #[test]
+    fn legacy_entry() {
+        let info = Terminfo::parse(EIGHT).unwrap();
+        assert_eq!(info.names()[0], "colorize-8");
+        assert_eq!(info.colors(), Some(8));
+        assert_eq!(info.number("pairs"), Some(64));
+        assert_eq!(info.setaf(), Some(&b"\x1b[3%p1%dm"[..]));
+        assert_eq!(info.setab(), Some(&b"\x1b[4%p1%dm"[..]));
+        assert_eq!(info.sitm(), None);
+        assert_eq!(info.smulx(), None);
+        assert!(!info.true_color());
+        assert_eq!(info.color_depth(), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn extended_entry() {
+        let info = Terminfo::parse(DIRECT).unwrap();
+        assert_eq!(info.colors(), Some(0x1000000));
+        assert_eq!(info.sitm(), Some(&b"\x1b[3m"[..]));
+        assert_eq!(info.smulx(), Some(&b"\x1b[4:%p1%dm"[..]));
+        assert!(info.flag("Tc") && info.flag("RGB"));
+        assert!(info.true_color());
+        assert_eq!(info.color_depth(), ColorDepth::TrueColor);
+    }
+
+    #[test]
+    fn entry_without_colors() {
+        let info = Terminfo::parse(MONO).unwrap();
+        assert_eq!(info.colors(), None);
+        assert_eq!(info.setaf(), None);
+        assert_eq!(info.smul(), Some(&b"\x1b[4m"[..]));
+        assert!(!info.true_color());
+    }
+
+    #[test]
+    fn invalid_entries() {
+        assert!(Terminfo::parse(&[]).is_err());
+        for len in &[4, 12, EIGHT.len() / 2, EIGHT.len() - 1] {
+            assert!(Terminfo::parse(&EIGHT[..*len]).is_err(), "truncated to {}", len);
+        }
+        let mut bad = EIGHT.to_vec();
+        bad[0] = 0x1b;
+        assert!(Terminfo::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn filter() {
+        let caps = Capabilities::of(&Terminfo::parse(EIGHT).unwrap());
+        assert_eq!(caps.filter(31), Some(31));
+        assert_eq!(caps.filter(92), Some(92));
+        assert_eq!(caps.filter(101), Some(101));
+        assert_eq!(caps.filter(1), Some(1));
+        assert_eq!(caps.filter(2), None);
+        assert_eq!(caps.filter(3), None);
+        assert_eq!(caps.filter(39), Some(39));
+
+        let caps = Capabilities::of(&Terminfo::parse(MONO).unwrap());
+        assert_eq!(caps.colors, 0);
+        assert_eq!(caps.filter(31), None);
+        assert_eq!(caps.filter(97), None);
+        assert_eq!(caps.filter(4), Some(4));
+        assert_eq!(caps.filter(0), Some(0));
+
+        let caps = Capabilities::of(&Terminfo::parse(DIRECT).unwrap());
+        assert!(caps.true_color && caps.italic && caps.faint);
+        assert_eq!(caps.filter(3), Some(3));
+        assert_eq!(Capabilities::ALL.filter(95), Some(95));
+    }
+}
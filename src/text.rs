@@ -0,0 +1,393 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Rich text made of spans of text sharing a style.
+//
+// ```Rust
+// let mut text = StyledString::from("error".red().bold());
+// text.push_str(": file not found");
+// let first_word = text.slice(0..5);
+// println!("{}", text.replace("file", "config"));
+// ```
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Bound, RangeBounds};
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use unicode_width::UnicodeWidthStr;
+
+use ansi::{self, Token};
+use paint::Paint;
+
+/// A back end writing the spans of a `StyledString`
+pub trait Render {
+    /// Write a text with its style
+    fn span(&mut self, paint: &Paint, text: &str);
+}
+
+/// An owned text made of styled spans
+///
+/// Adjacent spans always have different styles and no span is empty.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct StyledString {
+    spans: Vec<(Paint, String)>
+}
+
+impl StyledString {
+    /// An empty text
+    pub fn new() -> StyledString {
+        StyledString { spans: Vec::new() }
+    }
+
+    /// A text with a single style
+    pub fn styled(text: &str, paint: Paint) -> StyledString {
+        let mut styled = StyledString::new();
+        styled.push_styled(text, paint);
+        styled
+    }
+
+    /// Read a text containing escape sequences, such as the output of the
+    /// `AnsiColor` methods
+    ///
+    /// Other escape sequences than colors and styles are dropped.
+    pub fn parse(text: &str) -> StyledString {
+        let mut styled = StyledString::new();
+        let mut paint = Paint::new();
+        for token in ansi::tokens(text) {
+            match token {
+                Token::Escape(escape) => paint = paint.apply_sgr(escape),
+                Token::Text(text) => styled.push_styled(text, paint)
+            }
+        }
+        styled
+    }
+
+    /// The spans, with their style
+    pub fn spans(&self) -> &[(Paint, String)] {
+        &self.spans
+    }
+
+    /// Append a text without style, escapes are not interpreted
+    pub fn push_str(&mut self, text: &str) {
+        self.push_styled(text, Paint::new())
+    }
+
+    /// Append a text with a style
+    pub fn push_styled(&mut self, text: &str, paint: Paint) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.0 == paint => last.1.push_str(text),
+            _ => self.spans.push((paint, String::from(text)))
+        }
+    }
+
+    /// Append the spans of another text
+    pub fn append(&mut self, other: &StyledString) {
+        for &(paint, ref text) in &other.spans {
+            self.push_styled(text, paint);
+        }
+    }
+
+    /// Is the text empty
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Number of visible characters
+    pub fn len_visible(&self) -> usize {
+        self.spans.iter().map(|s| s.1.chars().count()).sum()
+    }
+
+    /// Number of terminal columns used to display the text
+    pub fn width(&self) -> usize {
+        self.spans.iter().map(|s| s.1.width()).sum()
+    }
+
+    /// The text without styles
+    pub fn to_plain(&self) -> String {
+        self.spans.iter().map(|s| &s.1[..]).collect()
+    }
+
+    /// The text with escape sequences, like the `AnsiColor` methods write it
+    ///
+    /// Like them it follows the terminal, the color depth, the cvd safe
+    /// mode and the global colors, so parsing it back may give other
+    /// spans. See `to_sgr` for an output independent of the terminal.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        self.render(&mut Ansi(&mut out));
+        out
+    }
+
+    /// The text with the escape sequences of `Paint::sgr`, parsed back
+    /// without loss
+    pub fn to_sgr(&self) -> String {
+        let mut out = String::new();
+        for (paint, text) in &self.spans {
+            // writing to a string can't fail
+            let _ = paint.write(&mut out, text);
+        }
+        out
+    }
+
+    /// Write the spans to a back end
+    pub fn render<R: Render + ?Sized>(&self, out: &mut R) {
+        for (paint, text) in &self.spans {
+            out.span(paint, text);
+        }
+    }
+
+    // The spans between two byte positions of the plain text
+    fn range(&self, start: usize, end: usize) -> StyledString {
+        let mut out = StyledString::new();
+        let mut pos = 0;
+        for &(paint, ref text) in &self.spans {
+            let (from, to) = (start.max(pos), end.min(pos + text.len()));
+            if from < to {
+                out.push_styled(&text[from - pos..to - pos], paint);
+            }
+            pos += text.len();
+        }
+        out
+    }
+
+    // The style at a byte position of the plain text
+    fn paint_at(&self, at: usize) -> Paint {
+        let mut pos = 0;
+        for &(paint, ref text) in &self.spans {
+            pos += text.len();
+            if at < pos {
+                return paint;
+            }
+        }
+        self.spans.last().map_or(Paint::new(), |s| s.0)
+    }
+
+    /// The spans of a range of visible characters
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> StyledString {
+        let plain = self.to_plain();
+        let byte = |i: usize| plain.char_indices().nth(i).map_or(plain.len(), |c| c.0);
+        let start = match range.start_bound() {
+            Bound::Included(&i) => byte(i),
+            Bound::Excluded(&i) => byte(i + 1),
+            Bound::Unbounded => 0
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&i) => byte(i + 1),
+            Bound::Excluded(&i) => byte(i),
+            Bound::Unbounded => plain.len()
+        };
+        self.range(start, end.max(start))
+    }
+
+    /// Split the text on a separator
+    pub fn split(&self, separator: &str) -> impl Iterator<Item = StyledString> {
+        let plain = self.to_plain();
+        let parts: Vec<StyledString> = plain.split(separator)
+            .map(|part| {
+                let start = part.as_ptr() as usize - plain.as_ptr() as usize;
+                self.range(start, start + part.len())
+            })
+            .collect();
+        parts.into_iter()
+    }
+
+    /// Split the text in lines, without their end of line
+    pub fn lines(&self) -> impl Iterator<Item = StyledString> {
+        let plain = self.to_plain();
+        let lines: Vec<StyledString> = plain.lines()
+            .map(|line| {
+                let start = line.as_ptr() as usize - plain.as_ptr() as usize;
+                self.range(start, start + line.len())
+            })
+            .collect();
+        lines.into_iter()
+    }
+
+    /// Replace every match of a text, the replacement takes the style of
+    /// the first character it replaces
+    pub fn replace(&self, from: &str, to: &str) -> StyledString {
+        if from.is_empty() {
+            return self.clone();
+        }
+        let plain = self.to_plain();
+        let mut out = StyledString::new();
+        let mut last = 0;
+        for (start, _) in plain.match_indices(from) {
+            out.append(&self.range(last, start));
+            out.push_styled(to, self.paint_at(start));
+            last = start + from.len();
+        }
+        out.append(&self.range(last, plain.len()));
+        out
+    }
+}
+
+// Writes the spans like the `AnsiColor` methods
+struct Ansi<'a>(&'a mut String);
+
+impl<'a> Render for Ansi<'a> {
+    fn span(&mut self, paint: &Paint, text: &str) {
+        if paint.is_plain() {
+            self.0.push_str(text);
+        } else {
+            self.0.push_str(&paint.paint(String::from(text)));
+        }
+    }
+}
+
+impl<'a> From<&'a str> for StyledString {
+    /// Read the escape sequences of the text
+    fn from(text: &'a str) -> StyledString {
+        StyledString::parse(text)
+    }
+}
+
+impl From<String> for StyledString {
+    /// Read the escape sequences of the text, so the output of the
+    /// `AnsiColor` methods converts without loss
+    fn from(text: String) -> StyledString {
+        StyledString::parse(&text)
+    }
+}
+
+impl fmt::Display for StyledString {
+    /// Written with escape sequences, see `to_ansi`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_ansi())
+    }
+}
+
+impl Add for StyledString {
+    type Output = StyledString;
+
+    fn add(mut self, other: StyledString) -> StyledString {
+        self.append(&other);
+        self
+    }
+}
+
+impl<'a> Add<&'a str> for StyledString {
+    type Output = StyledString;
+
+    /// Append a text without style
+    fn add(mut self, other: &'a str) -> StyledString {
+        self.push_str(other);
+        self
+    }
+}
+
+impl AddAssign for StyledString {
+    fn add_assign(&mut self, other: StyledString) {
+        self.append(&other);
+    }
+}
+
+impl<'a> AddAssign<&'a str> for StyledString {
+    fn add_assign(&mut self, other: &'a str) {
+        self.push_str(other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::StyledString;
+    use Color::{Red, Blue};
+    use paint::Paint;
+
+    const RED: Paint = Paint::new().fg(Red);
+    const BOLD_RED: Paint = Paint::new().fg(Red).bold();
+
+    fn spans(text: &StyledString) -> Vec<(Paint, &str)> {
+        text.spans().iter().map(|s| (s.0, &s.1[..])).collect()
+    }
+
+    #[test]
+    fn parse() {
+        let text = StyledString::parse("\x1b[1;31merror\x1b[0;39;49m: \x1b]0;title\x07file \x1b[34;22mnot\x1b[m found");
+        assert_eq!(spans(&text), [(BOLD_RED, "error"), (Paint::new(), ": file "),
+                                  (Paint::new().fg(Blue), "not"), (Paint::new(), " found")]);
+        assert_eq!(text.to_plain(), "error: file not found");
+        assert_eq!(text.len_visible(), 21);
+        assert_eq!(spans(&StyledString::parse("\x1b[38;5;196;48;2;1;2;3mx")),
+                   [(Paint::new().fg_rgb(255, 0, 0).bg_rgb(1, 2, 3), "x")]);
+        assert!(StyledString::parse("\x1b[31m\x1b[0m").is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = StyledString::parse("\x1b[1;31merror\x1b[0;39;49m: \x1b[4;44mnot\x1b[0;39;49m found");
+        assert_eq!(text.to_sgr(), "\x1b[31;1merror\x1b[0m: \x1b[44;4mnot\x1b[0m found");
+        assert_eq!(StyledString::from(text.to_sgr()), text);
+        let mut built = StyledString::styled("error", BOLD_RED);
+        built.push_str(": ");
+        built.push_styled("here", Paint::new().fg_rgb(1, 2, 3).italic());
+        assert_eq!(StyledString::from(built.to_sgr()), built);
+        assert_eq!(StyledString::from(built.to_ansi()).to_plain(), "error: here");
+        assert_eq!(StyledString::from(String::from("plain")), StyledString::styled("plain", Paint::new()));
+    }
+
+    #[test]
+    fn slice() {
+        let mut text = StyledString::styled("héllo ", RED);
+        text.push_str("world");
+        assert_eq!(spans(&text.slice(1..8)), [(RED, "éllo "), (Paint::new(), "wo")]);
+        assert_eq!(spans(&text.slice(..2)), [(RED, "hé")]);
+        assert_eq!(spans(&text.slice(6..)), [(Paint::new(), "world")]);
+        assert_eq!(spans(&text.slice(4..=6)), [(RED, "o "), (Paint::new(), "w")]);
+        let (start, end) = (8, 3);
+        assert!(text.slice(start..end).is_empty());
+        assert!(text.slice(20..).is_empty());
+    }
+
+    #[test]
+    fn split() {
+        let mut text = StyledString::styled("a,b", RED);
+        text.push_str(",c,");
+        let parts: Vec<StyledString> = text.split(",").collect();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(spans(&parts[0]), [(RED, "a")]);
+        assert_eq!(spans(&parts[1]), [(RED, "b")]);
+        assert_eq!(spans(&parts[2]), [(Paint::new(), "c")]);
+        assert!(parts[3].is_empty());
+        let lines: Vec<String> = StyledString::styled("one\r\ntwo\n", RED).lines().map(|l| l.to_plain()).collect();
+        assert_eq!(lines, ["one", "two"]);
+    }
+
+    #[test]
+    fn replace() {
+        let mut text = StyledString::styled("file", RED);
+        text.push_str(" not found, file");
+        let replaced = text.replace("file", "config");
+        assert_eq!(spans(&replaced), [(RED, "config"), (Paint::new(), " not found, config")]);
+        // a match across spans takes the style of its first character
+        assert_eq!(spans(&text.replace("e n", "_")), [(RED, "fil_"), (Paint::new(), "ot found, file")]);
+        assert_eq!(text.replace("", "x"), text);
+        assert_eq!(spans(&text.replace("file", "")), [(Paint::new(), " not found, ")]);
+    }
+}
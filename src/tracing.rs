@@ -0,0 +1,329 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Event formatter for `tracing-subscriber`, enabled by the `tracing` feature.
+//
+// ```Rust
+// let formatter = colorize::tracing::Formatter::new().stream(Stream::Stdout);
+// tracing_subscriber::fmt()
+//     .fmt_fields(formatter.clone())
+//     .event_format(formatter)
+//     .init();
+// ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::{FormatEvent, FormatFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+use Color::{BrightRed, Yellow, Green, Blue, BrightBlack};
+use Style::{Bold, Faint, Italic};
+use ansi;
+use internal;
+use term::{self, Stream};
+use time::timestamp;
+
+/// Colors and styles of the parts of an event
+///
+/// Every method has a default, override the ones to change.
+pub trait Theme: Send + Sync {
+    /// The level of the event, padded to 5 characters
+    fn level(&self, level: Level, text: String) -> String {
+        let color = match level {
+            Level::ERROR => BrightRed,
+            Level::WARN => Yellow,
+            Level::INFO => Green,
+            Level::DEBUG => Blue,
+            Level::TRACE => BrightBlack
+        };
+        internal::pack(color, text)
+    }
+    /// The time of the event
+    fn timestamp(&self, text: String) -> String { internal::pack(Faint, text) }
+    /// The name of a span the event is in
+    fn span(&self, text: String) -> String { internal::pack(Bold, text) }
+    /// The target of the event
+    fn target(&self, text: String) -> String { internal::pack(Faint, text) }
+    /// The message of the event
+    fn message(&self, text: String) -> String { text }
+    /// The name of a field
+    fn field_name(&self, text: String) -> String { internal::pack(Italic, text) }
+    /// The value of a field
+    fn field_value(&self, text: String) -> String { text }
+}
+
+/// The default theme
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultTheme;
+
+impl Theme for DefaultTheme {}
+
+/// A `FormatEvent` and `FormatFields` writing events with the colors of a theme
+///
+/// Use it as the field formatter too so the fields of spans are themed.
+#[derive(Clone)]
+pub struct Formatter {
+    theme: Arc<dyn Theme>,
+    colored: bool,
+    timestamp: bool,
+    target: bool
+}
+
+impl Default for Formatter {
+    fn default() -> Formatter {
+        Formatter::new()
+    }
+}
+
+impl Formatter {
+    /// Create a formatter without colors
+    ///
+    /// The writer of the events is unknown, enable the colors with `stream`
+    /// or `ansi`.
+    pub fn new() -> Formatter {
+        Formatter {
+            theme: Arc::new(DefaultTheme),
+            colored: false,
+            timestamp: true,
+            target: true
+        }
+    }
+
+    /// Color the events if colors are allowed for a writer on `stream`
+    pub fn stream(mut self, stream: Stream) -> Formatter {
+        self.colored = term::colors_enabled(stream);
+        self
+    }
+
+    /// Force the colors on or off
+    ///
+    /// Colors are never written to a writer without ansi escapes.
+    pub fn ansi(mut self, colored: bool) -> Formatter {
+        self.colored = colored;
+        self
+    }
+
+    /// Set the theme
+    pub fn theme<T: Theme + 'static>(mut self, theme: T) -> Formatter {
+        self.theme = Arc::new(theme);
+        self
+    }
+
+    /// Write the time of each event
+    pub fn timestamp(mut self, timestamp: bool) -> Formatter {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Write the target of each event
+    pub fn target(mut self, target: bool) -> Formatter {
+        self.target = target;
+        self
+    }
+
+    // The theme to use with a writer, none without colors
+    fn theme_for(&self, writer: &Writer<'_>) -> Option<&dyn Theme> {
+        if self.colored && writer.has_ansi_escapes() { Some(&*self.theme) } else { None }
+    }
+}
+
+fn paint<F: Fn(&dyn Theme, String) -> String>(theme: Option<&dyn Theme>, text: String, f: F) -> String {
+    match theme {
+        Some(theme) => f(theme, text),
+        None => text
+    }
+}
+
+// Collect the message and the other fields of an event or a span
+struct Fields<'a> {
+    theme: Option<&'a dyn Theme>,
+    message: String,
+    rest: String
+}
+
+impl<'a> Visit for Fields<'a> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let value = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = paint(self.theme, value, |t, s| t.message(s));
+        } else {
+            let name = paint(self.theme, String::from(field.name()), |t, s| t.field_name(s));
+            let value = paint(self.theme, value, |t, s| t.field_value(s));
+            self.rest.push_str(&format!(" {}={}", name, value));
+        }
+    }
+}
+
+impl<'writer> FormatFields<'writer> for Formatter {
+    fn format_fields<R: RecordFields>(&self, mut writer: Writer<'writer>, fields: R) -> fmt::Result {
+        let mut visitor = Fields { theme: self.theme_for(&writer), message: String::new(), rest: String::new() };
+        fields.record(&mut visitor);
+        let line = visitor.message + &visitor.rest;
+        write!(writer, "{}", line.trim_start())
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for Formatter
+    where S: Subscriber + for<'a> LookupSpan<'a>,
+          N: for<'a> FormatFields<'a> + 'static {
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>,
+                    event: &Event<'_>) -> fmt::Result {
+        let theme = self.theme_for(&writer);
+        let meta = event.metadata();
+        if self.timestamp {
+            write!(writer, "{} ", paint(theme, timestamp(), |t, s| t.timestamp(s)))?;
+        }
+        let level = format!("{:<5}", meta.level());
+        write!(writer, "{} ", paint(theme, level, |t, s| t.level(*meta.level(), s)))?;
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let name = paint(theme, String::from(span.name()), |t, s| t.span(s));
+                write!(writer, "{}", name)?;
+                let ext = span.extensions();
+                if let Some(fields) = ext.get::<FormattedFields<N>>() {
+                    // the fields were colored by the field formatter
+                    let fields = match theme {
+                        Some(_) => fields.fields.clone(),
+                        None => ansi::strip(&fields.fields)
+                    };
+                    if !fields.is_empty() {
+                        write!(writer, "{{{}}}", fields)?;
+                    }
+                }
+                write!(writer, ": ")?;
+            }
+        }
+        if self.target {
+            let target = format!("{}:", meta.target());
+            write!(writer, "{} ", paint(theme, target, |t, s| t.target(s)))?;
+        }
+
+        let mut fields = Fields { theme, message: String::new(), rest: String::new() };
+        event.record(&mut fields);
+        let line = fields.message + &fields.rest;
+        match theme {
+            Some(_) => writeln!(writer, "{}", line),
+            // messages built with colors stay clean in log files
+            None => writeln!(writer, "{}", ansi::strip(&line))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate tracing;
+
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::{Formatter, Theme};
+    use ansi;
+    use internal;
+    use Color::Green;
+    use Style::{Bold, Faint, Italic};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Buffer {
+            self.clone()
+        }
+    }
+
+    // Format the events emitted by `f` into a string
+    fn capture<F: FnOnce()>(formatter: Formatter, ansi: bool, f: F) -> String {
+        let buffer = Buffer::default();
+        let subscriber = ::tracing_subscriber::fmt()
+            .with_writer(buffer.clone())
+            .with_ansi(ansi)
+            .fmt_fields(formatter.clone())
+            .event_format(formatter)
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+        let out = buffer.0.lock().unwrap().clone();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn uncolored() {
+        let formatter = Formatter::new().timestamp(false);
+        let out = capture(formatter, true, || {
+            let span = tracing::info_span!("req", id = 7);
+            let _guard = span.enter();
+            tracing::info!(target: "app", user = "ann", "hello \x1b[1mworld\x1b[0m");
+        });
+        assert_eq!(out, "INFO  req{id=7}: app: hello world user=\"ann\"\n");
+    }
+
+    #[test]
+    fn colored() {
+        let formatter = Formatter::new().ansi(true).timestamp(false).target(false);
+        let out = capture(formatter, true, || tracing::info!(n = 1, "hi"));
+        let expected = format!("{} hi {}=1\n", internal::pack(Green, "INFO ".to_string()),
+                               internal::pack(Italic, "n".to_string()));
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn writer_without_ansi() {
+        let formatter = Formatter::new().ansi(true).timestamp(false);
+        let out = capture(formatter, false, || tracing::warn!(target: "app", "hi"));
+        assert_eq!(out, "WARN  app: hi\n");
+    }
+
+    struct Plain;
+
+    impl Theme for Plain {
+        fn level(&self, _: ::tracing_core::Level, text: String) -> String { text }
+        fn target(&self, text: String) -> String { internal::pack(Bold, text) }
+    }
+
+    #[test]
+    fn theme() {
+        let formatter = Formatter::new().ansi(true).theme(Plain);
+        let out = capture(formatter, true, || tracing::error!(target: "app", "hi"));
+        let (time, rest) = out.split_at(out.find(' ').unwrap());
+        assert!(ansi::strip(time).ends_with('Z'));
+        assert_eq!(time, internal::pack(Faint, ansi::strip(time)));
+        assert_eq!(rest, format!(" ERROR {} hi\n", internal::pack(Bold, "app:".to_string())));
+    }
+}
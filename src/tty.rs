@@ -0,0 +1,212 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Requests sent to the terminal and the replies read back from it.
+//
+// The terminal is reached through the `Tty` trait, implemented by `DevTty`
+// for the controlling terminal and by `Scripted` to replay fixed replies.
+
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant};
+
+use mux;
+
+/// A terminal requests are written to and replies are read from
+pub trait Tty {
+    /// Write all the bytes of a request
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()>;
+
+    /// Read the available bytes, waiting at most `timeout`
+    ///
+    /// Returns 0 when nothing was received in time.
+    fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize>;
+}
+
+impl<T: Tty + ?Sized> Tty for &mut T {
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        (**self).write_all(bytes)
+    }
+
+    fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+        (**self).read_timeout(buf, timeout)
+    }
+}
+
+/// A fake terminal replaying fixed bytes, and recording the requests
+#[derive(Clone, Debug, Default)]
+pub struct Scripted {
+    input: VecDeque<u8>,
+    /// Everything written to the terminal
+    pub written: Vec<u8>
+}
+
+impl Scripted {
+    /// Create a terminal which will reply with `input`
+    pub fn new(input: &[u8]) -> Scripted {
+        Scripted { input: input.iter().cloned().collect(), written: Vec::new() }
+    }
+}
+
+impl Tty for Scripted {
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.written.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn read_timeout(&mut self, buf: &mut [u8], _: Duration) -> io::Result<usize> {
+        let n = buf.len().min(self.input.len());
+        for (dst, src) in buf.iter_mut().zip(self.input.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+}
+
+/// The controlling terminal, in raw mode until dropped
+#[cfg(unix)]
+pub struct DevTty {
+    file: ::std::fs::File,
+    saved: ::libc::termios
+}
+
+#[cfg(unix)]
+impl DevTty {
+    /// Open `/dev/tty` and disable line buffering and echo
+    pub fn open() -> io::Result<DevTty> {
+        use std::os::unix::io::AsRawFd;
+
+        let file = ::std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+        let fd = file.as_raw_fd();
+        let mut saved: ::libc::termios = unsafe { ::std::mem::zeroed() };
+        if unsafe { ::libc::tcgetattr(fd, &mut saved) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = saved;
+        raw.c_lflag &= !(::libc::ICANON | ::libc::ECHO);
+        raw.c_cc[::libc::VMIN] = 1;
+        raw.c_cc[::libc::VTIME] = 0;
+        if unsafe { ::libc::tcsetattr(fd, ::libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(DevTty { file, saved })
+    }
+}
+
+#[cfg(unix)]
+impl Tty for DevTty {
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        use std::io::Write;
+
+        self.file.write_all(bytes)?;
+        self.file.flush()
+    }
+
+    fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> io::Result<usize> {
+        use std::io::Read;
+        use std::os::unix::io::AsRawFd;
+
+        let mut poll = ::libc::pollfd { fd: self.file.as_raw_fd(), events: ::libc::POLLIN, revents: 0 };
+        let ms = timeout.as_millis().min(i32::MAX as u128) as ::libc::c_int;
+        match unsafe { ::libc::poll(&mut poll, 1, ms) } {
+            -1 => Err(io::Error::last_os_error()),
+            0 => Ok(0),
+            _ => self.file.read(buf)
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for DevTty {
+    fn drop(&mut self) {
+        use std::os::unix::io::AsRawFd;
+
+        unsafe { ::libc::tcsetattr(self.file.as_raw_fd(), ::libc::TCSANOW, &self.saved) };
+    }
+}
+
+/// Read an escape sequence terminated by BEL or ST, waiting at most `timeout`
+///
+/// Returns the bytes between the introducer, such as `ESC ]`, and the
+/// terminator, or `None` if the terminal did not reply in time.
+pub fn read_reply<T: Tty>(tty: &mut T, timeout: Duration) -> io::Result<Option<String>> {
+    let deadline = Instant::now() + timeout;
+    let mut reply = Vec::new();
+    let mut byte = [0u8];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left == Duration::from_secs(0) || tty.read_timeout(&mut byte, left)? == 0 {
+            return Ok(None);
+        }
+        reply.push(byte[0]);
+        // skip the introducer, ESC and the next byte
+        if reply.len() <= 2 {
+            if reply[0] != 0x1b {
+                reply.clear();
+            }
+            continue;
+        }
+        if reply.ends_with(b"\x07") || reply.ends_with(b"\x1b\\") {
+            let end = reply.len() - if reply.ends_with(b"\x07") { 1 } else { 2 };
+            return Ok(Some(String::from_utf8_lossy(&reply[2..end]).into_owned()));
+        }
+    }
+}
+
+/// Send an OSC request, such as `11;?`, and read the reply
+pub fn query_osc<T: Tty>(tty: &mut T, request: &str, timeout: Duration) -> io::Result<Option<String>> {
+    tty.write_all(mux::passthrough(&format!("\x1b]{}\x07", request)).as_bytes())?;
+    read_reply(tty, timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{query_osc, read_reply, Scripted};
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn replies() {
+        let mut tty = Scripted::new(b"\x1b]11;rgb:0000/0000/0000\x07");
+        assert_eq!(read_reply(&mut tty, TIMEOUT).unwrap().as_deref(), Some("11;rgb:0000/0000/0000"));
+        let mut tty = Scripted::new(b"\x1b]10;rgb:ffff/ffff/ffff\x1b\\");
+        assert_eq!(read_reply(&mut tty, TIMEOUT).unwrap().as_deref(), Some("10;rgb:ffff/ffff/ffff"));
+        // bytes typed before the reply are skipped
+        let mut tty = Scripted::new(b"ab\x1b]4;1;#ff0000\x07");
+        assert_eq!(read_reply(&mut tty, TIMEOUT).unwrap().as_deref(), Some("4;1;#ff0000"));
+    }
+
+    #[test]
+    fn no_reply() {
+        assert_eq!(read_reply(&mut Scripted::new(b""), TIMEOUT).unwrap(), None);
+        // an unterminated reply is not returned
+        assert_eq!(read_reply(&mut Scripted::new(b"\x1b]11;rgb:00"), TIMEOUT).unwrap(), None);
+    }
+
+    #[test]
+    fn requests() {
+        let mut tty = Scripted::new(b"\x1b]11;rgb:1/2/3\x07");
+        assert_eq!(query_osc(&mut tty, "11;?", TIMEOUT).unwrap().as_deref(), Some("11;rgb:1/2/3"));
+        assert_eq!(tty.written, b"\x1b]11;?\x07");
+    }
+}
@@ -0,0 +1,293 @@
+// The MIT License (MIT)
+//
+// Copyright (c) 2014 Jeremy Letang
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy of
+// this software and associated documentation files (the "Software"), to deal in
+// the Software without restriction, including without limitation the rights to
+// use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+// the Software, and to permit persons to whom the Software is furnished to do so,
+// subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+// FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+// COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+// IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+// CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+
+// Word wrap and truncation of colored texts.
+//
+// The styles active at a line break are closed at the end of the line and
+// opened again at the start of the next one, so each line can be printed
+// on its own.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use ansi::{self, Token};
+use internal;
+
+// A grapheme with the escapes written before it
+struct Cell<'a> {
+    text: &'a str,
+    width: usize,
+    escapes: String,
+    // index of the styles active once the escapes are applied
+    state: usize
+}
+
+struct Cells<'a> {
+    cells: Vec<Cell<'a>>,
+    // escapes following the last grapheme
+    tail: String,
+    // active sgr sequences, concatenated
+    states: Vec<String>
+}
+
+fn cells(text: &str) -> Cells<'_> {
+    let mut cells = Vec::new();
+    let mut escapes = String::new();
+    let mut states = vec![String::new()];
+    for token in ansi::tokens(text) {
+        match token {
+            Token::Escape(s) => {
+                escapes.push_str(s);
+                if ansi::is_sgr(s) {
+                    let mut state = if ansi::is_reset(s) {
+                        String::new()
+                    } else {
+                        states[states.len() - 1].clone()
+                    };
+                    // a plain reset, or one back to the global colors, clears the state
+                    if s != "\x1b[0m" && s != "\x1b[m" && s != reset() {
+                        state.push_str(s);
+                    }
+                    states.push(state);
+                }
+            }
+            Token::Text(s) => for g in s.graphemes(true) {
+                cells.push(Cell {
+                    text: g,
+                    width: g.width(),
+                    escapes: escapes.clone(),
+                    state: states.len() - 1
+                });
+                escapes.clear();
+            }
+        }
+    }
+    Cells { cells, tail: escapes, states }
+}
+
+fn reset() -> String {
+    let (fg, bg) = internal::get_glob();
+    format!("\x1b[0;{};{}m", fg, bg)
+}
+
+impl<'a> Cells<'a> {
+    // Render the cells of a line, reopening and closing the active styles
+    fn line(&self, cells: &[Cell<'a>], close: bool) -> String {
+        let mut out = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i == 0 {
+                // sgr sequences are summed up by the state, keep the others
+                for token in ansi::tokens(&cell.escapes) {
+                    match token {
+                        Token::Escape(s) if !ansi::is_sgr(s) => out.push_str(s),
+                        _ => {}
+                    }
+                }
+                out.push_str(&self.states[cell.state]);
+            } else {
+                out.push_str(&cell.escapes);
+            }
+            out.push_str(cell.text);
+        }
+        match cells.last() {
+            Some(last) if close && !self.states[last.state].is_empty() => {
+                out.push_str(&reset())
+            }
+            _ => {}
+        }
+        out
+    }
+}
+
+fn is_space(cell: &Cell) -> bool {
+    cell.text.chars().all(|c| c.is_whitespace() && c != '\n')
+}
+
+/// Word wrap a text so that each line fits in `width` visible columns
+///
+/// Words longer than `width` are split, existing line breaks are kept.
+/// A grapheme wider than `width` can't be split: it is put alone on a
+/// line, which overflows.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let all = cells(text);
+    let cells = &all.cells;
+    let mut lines = Vec::new();
+    // start of the current line, and its width
+    let mut start = 0;
+    let mut line_width = 0;
+    let mut i = 0;
+    while i < cells.len() {
+        if cells[i].text == "\n" || cells[i].text == "\r\n" {
+            lines.push(all.line(&cells[start..i], true));
+            i += 1;
+            start = i;
+            line_width = 0;
+            continue;
+        }
+        // next word, with the spaces before it
+        let mut end = i;
+        while end < cells.len() && is_space(&cells[end]) {
+            end += 1;
+        }
+        let spaces: usize = cells[i..end].iter().map(|c| c.width).sum();
+        let word_start = end;
+        while end < cells.len() && !is_space(&cells[end]) && cells[end].text != "\n"
+            && cells[end].text != "\r\n" {
+            end += 1;
+        }
+        let word: usize = cells[word_start..end].iter().map(|c| c.width).sum();
+
+        if line_width + spaces + word <= width || word_start == end {
+            line_width += spaces + word;
+            i = end;
+        } else if word <= width && line_width > 0 {
+            lines.push(all.line(&cells[start..i], true));
+            start = word_start;
+            line_width = word;
+            i = end;
+        } else {
+            // the word does not fit on a line, split it
+            if line_width > 0 {
+                lines.push(all.line(&cells[start..i], true));
+                start = word_start;
+                line_width = 0;
+            } else {
+                line_width = spaces;
+            }
+            i = word_start;
+            while i < end {
+                if line_width + cells[i].width > width && line_width > 0 {
+                    lines.push(all.line(&cells[start..i], true));
+                    start = i;
+                    line_width = 0;
+                }
+                line_width += cells[i].width;
+                i += 1;
+            }
+        }
+    }
+    let mut last = all.line(&cells[start..], false);
+    last.push_str(&all.tail);
+    if !all.states[all.states.len() - 1].is_empty() {
+        last.push_str(&reset());
+    }
+    lines.push(last);
+    lines
+}
+
+/// Cut a text to `width` visible columns, ending it with `ellipsis` if needed
+///
+/// The ellipsis is cut too when it is wider than `width`.
+pub fn truncate(text: &str, width: usize, ellipsis: &str) -> String {
+    if ansi::visible_width(text) <= width {
+        return String::from(text);
+    }
+    let cut;
+    let ellipsis = if ansi::visible_width(ellipsis) > width {
+        cut = truncate(ellipsis, width, "");
+        &cut
+    } else {
+        ellipsis
+    };
+    let all = cells(text);
+    let room = width - ansi::visible_width(ellipsis);
+    let mut used = 0;
+    let mut end = 0;
+    for cell in all.cells.iter() {
+        if used + cell.width > room {
+            break;
+        }
+        used += cell.width;
+        end += 1;
+    }
+    let mut out = String::new();
+    let mut state = 0;
+    for cell in all.cells[..end].iter() {
+        out.push_str(&cell.escapes);
+        out.push_str(cell.text);
+        state = cell.state;
+    }
+    if let Some(cell) = all.cells.get(end) {
+        // styles starting right before the cut apply to the ellipsis too
+        out.push_str(&cell.escapes);
+        state = cell.state;
+    }
+    out.push_str(ellipsis);
+    if !all.states[state].is_empty() {
+        out.push_str(&reset());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{truncate, wrap};
+    use ansi;
+
+    #[test]
+    fn wrap_words() {
+        assert_eq!(wrap("the quick brown fox", 10), vec!["the quick", "brown fox"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("one\ntwo", 10), vec!["one", "two"]);
+    }
+
+    #[test]
+    fn wrap_reopens_styles() {
+        let lines = wrap("\x1b[31mred text\x1b[0m plain", 5);
+        assert_eq!(lines, vec!["\x1b[31mred\x1b[0;39;49m",
+                               "\x1b[31mtext\x1b[0;39;49m",
+                               "plain"]);
+        let lines = wrap("\x1b[1m\x1b[32mbold green words", 10);
+        assert_eq!(lines, vec!["\x1b[1m\x1b[32mbold green\x1b[0;39;49m",
+                               "\x1b[1m\x1b[32mwords\x1b[0;39;49m"]);
+    }
+
+    #[test]
+    fn wrap_wide_characters() {
+        for line in wrap("日本語のテキスト", 5) {
+            assert!(ansi::visible_width(&line) <= 5);
+        }
+    }
+
+    #[test]
+    fn wrap_overflowing_grapheme() {
+        assert_eq!(wrap("日本", 1), vec!["日", "本"]);
+        assert_eq!(wrap("a日b", 1), vec!["a", "日", "b"]);
+    }
+
+    #[test]
+    fn truncate_styled() {
+        assert_eq!(truncate("hello", 10, "…"), "hello");
+        assert_eq!(truncate("hello world", 6, "…"), "hello…");
+        assert_eq!(truncate("\x1b[31mhello world", 6, "…"), "\x1b[31mhello…\x1b[0;39;49m");
+        assert_eq!(truncate("ab\x1b[1mcdef", 3, "…"), "ab\x1b[1m…\x1b[0;39;49m");
+    }
+
+    #[test]
+    fn truncate_cuts_the_ellipsis() {
+        let cut = truncate("\x1b[31mhello world…", 1, "...");
+        assert_eq!(ansi::visible_width(&cut), 1);
+        assert_eq!(cut, "\x1b[31m.\x1b[0;39;49m");
+        assert_eq!(truncate("hello", 0, "..."), "");
+        assert_eq!(truncate("hello", 2, "..."), "..");
+    }
+}